@@ -13,10 +13,9 @@ pub struct ColorScheme {
     pub regular_files: fn(&str) -> ColoredString,
 }
 
-/// Predefined color schemes
-impl ColorScheme {
-    /// Default color scheme (current implementation)
-    pub fn default() -> Self {
+/// Default color scheme (current implementation)
+impl Default for ColorScheme {
+    fn default() -> Self {
         Self {
             hidden_files: |s| s.bright_black(),
             directories: |s| s.blue().bold(),
@@ -24,7 +23,10 @@ impl ColorScheme {
             regular_files: |s| s.normal(),
         }
     }
-    
+}
+
+/// Predefined color schemes
+impl ColorScheme {
     /// High contrast color scheme for better accessibility
     pub fn high_contrast() -> Self {
         Self {
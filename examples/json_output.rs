@@ -3,11 +3,11 @@
 //! This example shows how to extend the file listing tool to support JSON output.
 //! Run with: cargo run --example json_output
 //!
-//! Note: This is a demonstration example that shows the structure without requiring
-//! external dependencies. To implement actual JSON output, you would add:
+//! Note: `fls -j/--json` is now built in (see `src/display/json.rs`); this
+//! example walks through the steps it followed, which apply to any new
+//! output format. It uses the same dependencies:
 //! - serde = { version = "1.0", features = ["derive"] }
 //! - serde_json = "1.0"
-//! to your Cargo.toml dependencies.
 
 /// Example of how to add JSON output to the existing codebase
 fn main() {
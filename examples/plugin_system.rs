@@ -89,6 +89,12 @@ pub struct PluginRegistry {
     plugins: HashMap<String, Box<dyn FileInfoPlugin>>,
 }
 
+impl Default for PluginRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PluginRegistry {
     pub fn new() -> Self {
         let mut registry = Self {
@@ -37,15 +37,16 @@ fn custom_tree_symbols() {
     const ALT_BRANCH: &str = "+-";
     const ALT_LAST: &str = "`-";
     const ALT_VERTICAL: &str = "| ";
+    #[allow(dead_code)]
     const ALT_SPACE: &str = "  ";
     
     println!("ASCII-style tree (for terminals without Unicode):");
     println!(".");
-    println!("+-Cargo.toml");
-    println!("+-src");
-    println!("| +-main.rs");
-    println!("| `-lib.rs");
-    println!("`-README.md\n");
+    println!("{}Cargo.toml", ALT_BRANCH);
+    println!("{}src", ALT_BRANCH);
+    println!("{}{}main.rs", ALT_VERTICAL, ALT_BRANCH);
+    println!("{}{}lib.rs", ALT_VERTICAL, ALT_LAST);
+    println!("{}README.md\n", ALT_LAST);
 }
 
 /// Example: Programmatic depth control
@@ -57,8 +58,8 @@ fn depth_control_example() {
     
     for depth in depths {
         println!("Tree with depth {}:", depth);
-        let output = Command::new("fls")
-            .args(&["-t", "-L", &depth.to_string()])
+        let _output = Command::new("fls")
+            .args(["-t", "-L", &depth.to_string()])
             .output()
             .expect("Failed to execute command");
         
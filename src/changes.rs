@@ -0,0 +1,298 @@
+//! Change detection between runs for `--changed`.
+//!
+//! Each run with `--changed` stores a fingerprint of the listed directory (the
+//! size, modification time, owner, group, and mode of every entry) under the
+//! XDG cache directory. The next run on the same path compares the directory
+//! against it to find new, modified, and removed entries, how much each
+//! entry grew or shrank, and entries whose ownership or permissions drifted.
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, DirEntry, Metadata};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::{debug, warn};
+
+use crate::file_info::{group_name, user_name};
+
+/// Characters escaped in fingerprint entry names, so each entry fits on one tab-separated line
+const NAME_ESCAPES: &AsciiSet = &CONTROLS.add(b'%').add(b'\t');
+
+/// What distinguishes one version of an entry from another.
+#[derive(PartialEq, Eq)]
+struct Fingerprint {
+    size: u64,
+    modified_nanos: u128,
+    /// None for fingerprints stored by versions that did not record ownership
+    ownership: Option<Ownership>,
+}
+
+/// Owner, group, and permission bits of an entry.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Ownership {
+    uid: u32,
+    gid: u32,
+    mode: u32,
+}
+
+impl Ownership {
+    fn of(metadata: &Metadata) -> Self {
+        Self {
+            uid: metadata.uid(),
+            gid: metadata.gid(),
+            mode: metadata.mode() & 0o7777,
+        }
+    }
+}
+
+/// Ownership and permission changes of one entry, as (previous, current) pairs.
+#[derive(Default)]
+pub struct Drift {
+    /// Previous and current owning user ID
+    pub owner: Option<(u32, u32)>,
+    /// Previous and current owning group ID
+    pub group: Option<(u32, u32)>,
+    /// Previous and current permission bits, including setuid, setgid, and sticky
+    pub mode: Option<(u32, u32)>,
+}
+
+impl Drift {
+    fn between(previous: Ownership, current: Ownership) -> Option<Self> {
+        let changed = |old: u32, new: u32| (old != new).then_some((old, new));
+        let drift = Self {
+            owner: changed(previous.uid, current.uid),
+            group: changed(previous.gid, current.gid),
+            mode: changed(previous.mode, current.mode),
+        };
+        (drift.owner.is_some() || drift.group.is_some() || drift.mode.is_some()).then_some(drift)
+    }
+
+    /// Describes the drift with user and group names and octal modes.
+    ///
+    /// # Returns
+    ///
+    /// A description like "owner alice→root, mode 644→666"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some((old, new)) = self.owner {
+            parts.push(format!("owner {}→{}", user_name(old), user_name(new)));
+        }
+        if let Some((old, new)) = self.group {
+            parts.push(format!("group {}→{}", group_name(old), group_name(new)));
+        }
+        if let Some((old, new)) = self.mode {
+            parts.push(format!("mode {:o}→{:o}", old, new));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Differences between the previous run and the current directory contents.
+#[derive(Default)]
+pub struct ChangeSet {
+    /// Entries that did not exist in the previous run
+    pub new: HashSet<String>,
+    /// Entries whose size or modification time changed
+    pub modified: HashSet<String>,
+    /// Entries that existed in the previous run but are gone, sorted by name
+    pub removed: Vec<String>,
+    /// Entries whose owner, group, or permissions changed, by name
+    pub drift: HashMap<String, Drift>,
+    /// Size growth (positive) or shrinkage (negative) in bytes of entries whose size changed, by name
+    pub size_deltas: HashMap<String, i64>,
+}
+
+impl ChangeSet {
+    /// Returns a short label for an entry's change, or `None` if it is unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The entry name
+    ///
+    /// # Returns
+    ///
+    /// `"new"` or `"modified"`, or `None` for unchanged entries
+    pub fn label(&self, name: &str) -> Option<&'static str> {
+        if self.new.contains(name) {
+            Some("new")
+        } else if self.modified.contains(name) {
+            Some("modified")
+        } else {
+            None
+        }
+    }
+
+    /// Describes everything that changed about an entry, for the Change column.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The entry name
+    ///
+    /// # Returns
+    ///
+    /// A description like "new", "modified", "mode 644→666", or
+    /// "modified, owner alice→root", or `None` for unchanged entries
+    pub fn describe(&self, name: &str) -> Option<String> {
+        let drift = self.drift.get(name).map(Drift::describe);
+        match (self.label(name), drift) {
+            (Some(label), Some(drift)) => Some(format!("{}, {}", label, drift)),
+            (Some(label), None) => Some(label.to_string()),
+            (None, drift) => drift,
+        }
+    }
+}
+
+/// Compares a directory against the fingerprint stored by the previous run and
+/// stores the current fingerprint for the next one.
+///
+/// On the first run for a path there is nothing to compare against, so the
+/// returned change set is empty.
+///
+/// # Arguments
+///
+/// * `path` - The listed directory
+/// * `entries` - The directory's entries
+///
+/// # Returns
+///
+/// The entries that are new, modified, or removed since the previous run, and
+/// those whose ownership or permissions drifted
+pub fn detect(path: &Path, entries: &[io::Result<DirEntry>]) -> ChangeSet {
+    let current: HashMap<String, Fingerprint> = entries
+        .iter()
+        .filter_map(|entry| entry.as_ref().ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let modified_nanos = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_nanos());
+            let fingerprint = Fingerprint {
+                size: metadata.len(),
+                modified_nanos,
+                ownership: Some(Ownership::of(&metadata)),
+            };
+            Some((entry.file_name().to_string_lossy().to_string(), fingerprint))
+        })
+        .collect();
+
+    let Some(cache_file) = cache_file(path) else {
+        warn!("cannot determine a cache directory for --changed (set XDG_CACHE_HOME or HOME)");
+        return ChangeSet::default();
+    };
+
+    let changes = match fs::read_to_string(&cache_file) {
+        Ok(contents) => compare(&parse(&contents), &current),
+        Err(_) => {
+            debug!("no previous listing of {} to compare against", path.display());
+            ChangeSet::default()
+        }
+    };
+
+    if let Err(e) = store(&cache_file, &current) {
+        warn!("cannot store listing fingerprint in {}: {}", cache_file.display(), e);
+    }
+    changes
+}
+
+fn compare(previous: &HashMap<String, Fingerprint>, current: &HashMap<String, Fingerprint>) -> ChangeSet {
+    let mut changes = ChangeSet::default();
+    for (name, fingerprint) in current {
+        match previous.get(name) {
+            None => {
+                changes.new.insert(name.clone());
+            }
+            Some(previous) => {
+                if (previous.size, previous.modified_nanos) != (fingerprint.size, fingerprint.modified_nanos) {
+                    changes.modified.insert(name.clone());
+                }
+                if previous.size != fingerprint.size {
+                    let delta = i128::from(fingerprint.size) - i128::from(previous.size);
+                    changes.size_deltas.insert(name.clone(), delta.clamp(i64::MIN.into(), i64::MAX.into()) as i64);
+                }
+                if let (Some(old), Some(new)) = (previous.ownership, fingerprint.ownership) {
+                    if let Some(drift) = Drift::between(old, new) {
+                        changes.drift.insert(name.clone(), drift);
+                    }
+                }
+            }
+        }
+    }
+    changes.removed = previous.keys().filter(|name| !current.contains_key(*name)).cloned().collect();
+    changes.removed.sort();
+    changes
+}
+
+/// Parses a stored fingerprint: one `name<TAB>size<TAB>mtime-nanos<TAB>uid<TAB>gid<TAB>mode`
+/// line per entry, where the ownership fields are missing in older fingerprints.
+fn parse(contents: &str) -> HashMap<String, Fingerprint> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let name = percent_decode_str(fields.next()?).decode_utf8_lossy().to_string();
+            let size = fields.next()?.parse().ok()?;
+            let modified_nanos = fields.next()?.parse().ok()?;
+            let ownership = (|| {
+                Some(Ownership {
+                    uid: fields.next()?.parse().ok()?,
+                    gid: fields.next()?.parse().ok()?,
+                    mode: u32::from_str_radix(fields.next()?, 8).ok()?,
+                })
+            })();
+            Some((name, Fingerprint { size, modified_nanos, ownership }))
+        })
+        .collect()
+}
+
+fn store(cache_file: &Path, current: &HashMap<String, Fingerprint>) -> io::Result<()> {
+    if let Some(parent) = cache_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut names: Vec<&String> = current.keys().collect();
+    names.sort();
+    let contents: String = names
+        .into_iter()
+        .map(|name| {
+            let fingerprint = &current[name];
+            let ownership = fingerprint
+                .ownership
+                .map(|ownership| format!("\t{}\t{}\t{:o}", ownership.uid, ownership.gid, ownership.mode))
+                .unwrap_or_default();
+            format!(
+                "{}\t{}\t{}{}\n",
+                utf8_percent_encode(name, NAME_ESCAPES),
+                fingerprint.size,
+                fingerprint.modified_nanos,
+                ownership
+            )
+        })
+        .collect();
+    fs::write(cache_file, contents)
+}
+
+/// Returns the fingerprint file for a directory under `$XDG_CACHE_HOME/fls/changed`.
+///
+/// Files are named by a hash of the canonical path, so different spellings of
+/// the same directory share one fingerprint.
+fn cache_file(path: &Path) -> Option<PathBuf> {
+    let cache_home = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let file_name = format!("{:016x}", fnv1a(canonical.as_os_str().as_encoded_bytes()));
+    Some(cache_home.join("fls").join("changed").join(file_name))
+}
+
+/// 64-bit FNV-1a hash, used because it is stable across Rust releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
@@ -0,0 +1,296 @@
+//! Checks for `--check-case-collisions`, `--check-names`, and
+//! `--check-owners`.
+//!
+//! Names that differ only by case, such as `README.md` and `readme.md`, can
+//! live side by side on most Linux filesystems but not on the case-insensitive
+//! ones macOS and Windows use by default, where a checkout silently keeps only
+//! one of them. Other names cannot be created on Windows at all (`CON`,
+//! `nul.txt`, names ending in a space or dot), or break scripts and tools
+//! (control characters, bytes that are not UTF-8). Files whose user or group
+//! ID no longer maps to an account are left behind when accounts are removed.
+//!
+//! Listed entries that fail a check are marked with a badge after their name,
+//! each problem is reported once on stderr, and `fls` exits with status 1
+//! when any was found.
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tracing::warn;
+use users::{get_group_by_gid, get_user_by_uid};
+
+use crate::config::Config;
+
+/// Badge written after the name of an entry that collides with a sibling
+pub const CASE_COLLISION_BADGE: &str = "≈";
+
+/// Badge written after a name that fails `--check-names`
+pub const NAME_PROBLEM_BADGE: &str = "⚠";
+
+/// Badge written after the name of an entry whose user or group does not exist
+pub const ORPHANED_OWNER_BADGE: &str = "⊘";
+
+/// Device names Windows reserves in every directory, with or without an extension
+const RESERVED_WINDOWS_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Whether any check failed during this run
+static CHECKS_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Returns the process-wide cache of the colliding names of each checked directory.
+fn collision_cache() -> &'static Mutex<HashMap<PathBuf, HashSet<String>>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, HashSet<String>>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Returns the process-wide cache of whether each checked path has a name problem.
+fn name_cache() -> &'static Mutex<HashMap<PathBuf, bool>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, bool>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Returns the process-wide cache of the owner problems of each checked path.
+fn owner_cache() -> &'static Mutex<HashMap<PathBuf, Vec<&'static str>>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, Vec<&'static str>>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Finds the names of a directory that differ from another name in it only
+/// by case, and reports each group of them.
+///
+/// All entries count, including hidden and filtered ones, since a checkout
+/// creates them all.
+fn colliding_names(directory: &Path) -> HashSet<String> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return HashSet::new();
+    };
+
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        groups.entry(name.to_lowercase()).or_default().push(name);
+    }
+
+    let mut collisions: Vec<Vec<String>> = groups.into_values().filter(|names| names.len() > 1).collect();
+    collisions.iter_mut().for_each(|names| names.sort());
+    collisions.sort();
+    for names in &collisions {
+        warn!("names in {} differ only by case: {}", directory.display(), names.join(", "));
+    }
+    if !collisions.is_empty() {
+        CHECKS_FAILED.store(true, Ordering::Relaxed);
+    }
+    collisions.into_iter().flatten().collect()
+}
+
+/// Checks whether an entry's name differs from a sibling's only by case.
+///
+/// Each directory is read once; its collisions are reported the first time
+/// one of its entries is checked.
+///
+/// # Arguments
+///
+/// * `path` - Path to the entry
+/// * `config` - Configuration specifying whether names are checked
+///
+/// # Returns
+///
+/// `true` if the entry collides, `false` without `--check-case-collisions`
+pub fn has_case_collision(path: &Path, config: &Config) -> bool {
+    if !config.check_case_collisions {
+        return false;
+    }
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut cache = collision_cache().lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry(directory.to_path_buf())
+        .or_insert_with(|| colliding_names(directory))
+        .contains(name.to_string_lossy().as_ref())
+}
+
+/// Describes what makes a file name unportable.
+///
+/// # Arguments
+///
+/// * `name` - The file name
+///
+/// # Returns
+///
+/// The problems found, e.g. `["reserved name on Windows"]`, or an empty vector
+pub fn name_problems(name: &OsStr) -> Vec<&'static str> {
+    let mut problems = Vec::new();
+    let Some(name) = name.to_str() else {
+        problems.push("invalid UTF-8");
+        return problems;
+    };
+
+    if name.chars().any(char::is_control) {
+        problems.push("control character");
+    }
+    if name.ends_with(' ') {
+        problems.push("trailing space");
+    } else if name.ends_with('.') && name != "." && name != ".." {
+        problems.push("trailing dot");
+    }
+    // Windows reserves the device names whatever the extension, so `nul.txt` is `NUL` too
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if RESERVED_WINDOWS_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+        problems.push("reserved name on Windows");
+    }
+    problems
+}
+
+/// Checks whether an entry's name fails `--check-names`, reporting its
+/// problems the first time the entry is checked.
+///
+/// # Arguments
+///
+/// * `path` - Path to the entry
+/// * `config` - Configuration specifying whether names are checked
+///
+/// # Returns
+///
+/// `true` if the name has a problem, `false` without `--check-names`
+pub fn has_name_problem(path: &Path, config: &Config) -> bool {
+    if !config.check_names {
+        return false;
+    }
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+
+    let mut cache = name_cache().lock().unwrap_or_else(|e| e.into_inner());
+    *cache.entry(path.to_path_buf()).or_insert_with(|| {
+        let problems = name_problems(name);
+        if problems.is_empty() {
+            return false;
+        }
+        // Quoted, so trailing spaces and control characters are visible
+        warn!("{:?}: {}", path, problems.join(", "));
+        CHECKS_FAILED.store(true, Ordering::Relaxed);
+        true
+    })
+}
+
+/// Describes which of an entry's owners have no account.
+///
+/// # Arguments
+///
+/// * `metadata` - The entry's metadata
+///
+/// # Returns
+///
+/// `"unknown user"` and/or `"unknown group"`, or an empty vector
+pub fn owner_problems(metadata: &fs::Metadata) -> Vec<&'static str> {
+    let mut problems = Vec::new();
+    if get_user_by_uid(metadata.uid()).is_none() {
+        problems.push("unknown user");
+    }
+    if get_group_by_gid(metadata.gid()).is_none() {
+        problems.push("unknown group");
+    }
+    problems
+}
+
+/// Finds which of an entry's owners fail `--check-owners`, reporting them
+/// the first time the entry is checked.
+///
+/// The entry itself is checked, not a symlink's target.
+///
+/// # Arguments
+///
+/// * `path` - Path to the entry
+/// * `config` - Configuration specifying whether owners are checked
+///
+/// # Returns
+///
+/// The problems (see [`owner_problems`]), or an empty vector without `--check-owners`
+pub fn orphaned_owners(path: &Path, config: &Config) -> Vec<&'static str> {
+    if !config.check_owners {
+        return Vec::new();
+    }
+
+    let mut cache = owner_cache().lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry(path.to_path_buf())
+        .or_insert_with(|| {
+            let Ok(metadata) = fs::symlink_metadata(path) else {
+                return Vec::new();
+            };
+            let problems = owner_problems(&metadata);
+            if !problems.is_empty() {
+                warn!("{:?}: {} (uid {}, gid {})", path, problems.join(", "), metadata.uid(), metadata.gid());
+                CHECKS_FAILED.store(true, Ordering::Relaxed);
+            }
+            problems
+        })
+        .clone()
+}
+
+/// Returns the badges of the checks an entry fails, e.g. ` ≈ ⚠ ⊘`, to be
+/// written after its name.
+///
+/// # Arguments
+///
+/// * `path` - Path to the entry
+/// * `config` - Configuration specifying the active checks
+///
+/// # Returns
+///
+/// The uncolored badges, each preceded by a space, or an empty string
+pub fn check_badges(path: &Path, config: &Config) -> String {
+    let mut badges = String::new();
+    if has_case_collision(path, config) {
+        badges.push(' ');
+        badges.push_str(CASE_COLLISION_BADGE);
+    }
+    if has_name_problem(path, config) {
+        badges.push(' ');
+        badges.push_str(NAME_PROBLEM_BADGE);
+    }
+    if !orphaned_owners(path, config).is_empty() {
+        badges.push(' ');
+        badges.push_str(ORPHANED_OWNER_BADGE);
+    }
+    badges
+}
+
+/// Describes the checks an entry fails, e.g. `["case collision", "trailing space"]`.
+///
+/// # Arguments
+///
+/// * `path` - Path to the entry
+/// * `config` - Configuration specifying the active checks
+///
+/// # Returns
+///
+/// The failed checks, or an empty vector
+pub fn failed_checks(path: &Path, config: &Config) -> Vec<&'static str> {
+    let mut failed = Vec::new();
+    if has_case_collision(path, config) {
+        failed.push("case collision");
+    }
+    if has_name_problem(path, config) {
+        failed.extend(path.file_name().map(name_problems).unwrap_or_default());
+    }
+    failed.extend(orphaned_owners(path, config));
+    failed
+}
+
+/// Returns whether any check failed, for the exit status.
+pub fn checks_failed() -> bool {
+    CHECKS_FAILED.load(Ordering::Relaxed)
+}
@@ -5,18 +5,56 @@
 //! color schemes based on file types and sizes.
 
 use colored::*;
-use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
+use percent_encoding::{percent_decode_str, percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::{debug, warn};
 
-use crate::file_info::{is_executable, FileInfo};
+use crate::file_info::{get_file_type, FileInfo, LINK_ARROW};
+use crate::formatting::parse_size;
+
+/// Reads the color preference from the environment.
+///
+/// Follows the CLICOLOR conventions: a non-zero `CLICOLOR_FORCE` forces colors
+/// even when output is redirected, while `NO_COLOR`, `CLICOLOR=0`, and
+/// `TERM=dumb` disable them.
+///
+/// # Returns
+///
+/// `Some(true)` to force colors, `Some(false)` to disable them, or `None` to
+/// decide by whether the output is a terminal
+pub fn color_preference() -> Option<bool> {
+    let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+
+    if var("CLICOLOR_FORCE").is_some_and(|value| value != "0") {
+        Some(true)
+    } else if var("NO_COLOR").is_some()
+        || var("CLICOLOR").is_some_and(|value| value == "0")
+        || var("TERM").is_some_and(|value| value == "dumb")
+    {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Applies the environment's color preference to all colored output.
+///
+/// Without a preference, colors are used only when stdout is a terminal.
+pub fn init_color_mode() {
+    if let Some(enabled) = color_preference() {
+        control::set_override(enabled);
+    }
+}
 
 /// Applies color formatting to a file name based on its type and attributes.
 ///
 /// # Arguments
 ///
 /// * `file_name` - The name of the file
-/// * `metadata` - The file's metadata
+/// * `path` - Path to the file, to check whether a symlink is broken
+/// * `metadata` - The file's own metadata (symlinks not followed)
 ///
 /// # Returns
 ///
@@ -24,19 +62,389 @@ use crate::file_info::{is_executable, FileInfo};
 ///
 /// # Color Scheme
 ///
+/// - Broken symlinks: Red and bold
 /// - Hidden files (starting with '.'): Dimmed/gray
 /// - Directories: Blue and bold
+/// - Symlinks: Cyan
+/// - FIFOs, sockets, and devices: see [`special_file_color`]
 /// - Executable files: Green and bold
 /// - Regular files: Normal color
-pub fn get_colored_name(file_name: &str, metadata: &fs::Metadata) -> String {
-    if file_name.starts_with('.') {
+///
+/// With `LS_COLORS` set, its colors are used instead (see [`Theme::from_ls_colors`]).
+pub fn get_colored_name(file_name: &str, path: &Path, metadata: &fs::Metadata) -> String {
+    let broken = metadata.file_type().is_symlink() && fs::metadata(path).is_err();
+    colored_name(file_name, &get_file_type(metadata), broken)
+}
+
+/// Colors a file name by its type, for [`get_colored_name`] and [`format_with_color`].
+fn colored_name(file_name: &str, file_type: &str, broken: bool) -> String {
+    let theme = theme();
+    if theme.ls_colors {
+        return paint(file_name, theme.ls_color(file_name, file_type, broken));
+    }
+    if broken {
+        format!("{}", file_name.red().bold())
+    } else if file_name.starts_with('.') {
         format!("{}", file_name.bright_black())
-    } else if metadata.is_dir() {
-        format!("{}", file_name.blue().bold())
-    } else if is_executable(metadata) {
-        format!("{}", file_name.green().bold())
+    } else if let Some(colored_name) = special_file_color(file_name, file_type) {
+        colored_name
+    } else {
+        match file_type {
+            "Directory" => format!("{}", file_name.blue().bold()),
+            "Symlink" => format!("{}", file_name.cyan()),
+            "Executable" => format!("{}", file_name.green().bold()),
+            _ => file_name.to_string(),
+        }
+    }
+}
+
+/// Formats the ` -> target` suffix shown after a symlink's name.
+///
+/// # Arguments
+///
+/// * `target` - Where the link points, as stored in the link
+/// * `broken` - Whether the target does not exist
+///
+/// # Returns
+///
+/// The suffix, with a broken target in red
+pub fn link_suffix(target: &str, broken: bool) -> String {
+    if broken {
+        format!(" {} {}", LINK_ARROW, target.red())
+    } else {
+        format!(" {} {}", LINK_ARROW, target)
+    }
+}
+
+/// Colors the name of a FIFO, socket, or device file, as `ls --color` does.
+///
+/// # Arguments
+///
+/// * `file_name` - The name of the file
+/// * `file_type` - The file type as returned by `get_file_type`
+///
+/// # Returns
+///
+/// The colored name, or `None` if the file is not one of these special types
+///
+/// # Color Scheme
+///
+/// The [`Theme`], by default as in `dircolors`:
+///
+/// - FIFOs (named pipes): Yellow on black
+/// - Sockets: Magenta and bold
+/// - Block and character devices: Yellow and bold on black
+pub fn special_file_color(file_name: &str, file_type: &str) -> Option<String> {
+    let theme = theme();
+    let sgr = match file_type {
+        "FIFO" => &theme.fifo,
+        "Socket" => &theme.socket,
+        "Block Device" => &theme.block_device,
+        "Character Device" => &theme.char_device,
+        _ => return None,
+    };
+    Some(paint(file_name, sgr))
+}
+
+/// Wraps text in an SGR escape sequence, unless colors are disabled.
+///
+/// # Arguments
+///
+/// * `text` - The text to color
+/// * `sgr` - SGR parameters in `dircolors` syntax, e.g. `01;35` for bold magenta
+///
+/// # Returns
+///
+/// The colored text, or the text unchanged if colors are off or `sgr` is empty
+fn paint(text: &str, sgr: &str) -> String {
+    if sgr.is_empty() || !control::SHOULD_COLORIZE.should_colorize() {
+        text.to_string()
     } else {
-        file_name.to_string()
+        format!("\x1b[{}m{}\x1b[0m", sgr, text)
+    }
+}
+
+/// Colors of file names by type, as SGR parameters in `dircolors` syntax.
+///
+/// The defaults are those of `dircolors`, so `/dev` and `/run` look as they
+/// do with `ls --color`. `LS_COLORS` replaces the whole scheme (see
+/// [`Theme::from_ls_colors`]), and the `[colors]` section of the
+/// configuration file changes the special file types on top of either:
+///
+/// ```toml
+/// [colors]
+/// fifo = "33"            # yellow
+/// socket = "01;35"       # bold magenta
+/// block-device = "01;33;44"
+/// char-device = "01;33"
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// Color of FIFOs (named pipes)
+    pub fifo: String,
+    /// Color of sockets
+    pub socket: String,
+    /// Color of block devices
+    pub block_device: String,
+    /// Color of character devices
+    pub char_device: String,
+    /// Whether the scheme comes from `LS_COLORS`, which also colors the other types
+    pub ls_colors: bool,
+    /// Color of directories (`di`)
+    pub directory: String,
+    /// Color of symlinks (`ln`)
+    pub symlink: String,
+    /// Color of broken symlinks (`or`), or None to use the symlink color
+    pub orphan: Option<String>,
+    /// Color of executable files (`ex`)
+    pub executable: String,
+    /// Color of other regular files (`fi`)
+    pub file: String,
+    /// Colors of regular files by name suffix (`*.tar=01;31`), lowercase,
+    /// in `LS_COLORS` order
+    pub suffixes: Vec<(String, String)>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            fifo: "40;33".to_string(),
+            socket: "01;35".to_string(),
+            block_device: "40;33;01".to_string(),
+            char_device: "40;33;01".to_string(),
+            ls_colors: false,
+            directory: "01;34".to_string(),
+            symlink: "01;36".to_string(),
+            orphan: None,
+            executable: "01;32".to_string(),
+            file: String::new(),
+            suffixes: Vec::new(),
+        }
+    }
+}
+
+impl Theme {
+    /// Parses a `dircolors` scheme from the value of `LS_COLORS`.
+    ///
+    /// Entries are `key=SGR` pairs separated by `:`. The type keys `di`,
+    /// `ln`, `or`, `pi`, `so`, `bd`, `cd`, `ex`, and `fi` (or `no`) color
+    /// entries by type, and `*suffix` keys color regular files by the end of
+    /// their name, case-insensitively, with later entries taking precedence.
+    /// Types missing from the variable keep the `dircolors` defaults. Other
+    /// keys and values that are not SGR parameters (such as `ln=target`) are
+    /// skipped.
+    ///
+    /// Unlike the built-in scheme, hidden files are not dimmed, as in `ls`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value of `LS_COLORS`
+    ///
+    /// # Returns
+    ///
+    /// The theme
+    pub fn from_ls_colors(value: &str) -> Self {
+        let mut theme = Self {
+            ls_colors: true,
+            ..Self::default()
+        };
+        for entry in value.split(':').filter(|entry| !entry.is_empty()) {
+            let Some((key, sgr)) = entry.split_once('=') else {
+                debug!("ignoring LS_COLORS entry without '=': {}", entry);
+                continue;
+            };
+            if !is_sgr(sgr) {
+                debug!("ignoring unsupported LS_COLORS entry: {}", entry);
+                continue;
+            }
+            let sgr = sgr.to_string();
+            match key {
+                "di" => theme.directory = sgr,
+                "ln" => theme.symlink = sgr,
+                "or" => theme.orphan = Some(sgr),
+                "pi" => theme.fifo = sgr,
+                "so" => theme.socket = sgr,
+                "bd" => theme.block_device = sgr,
+                "cd" => theme.char_device = sgr,
+                "ex" => theme.executable = sgr,
+                "fi" | "no" => theme.file = sgr,
+                _ => match key.strip_prefix('*') {
+                    Some(suffix) => theme.suffixes.push((suffix.to_lowercase(), sgr)),
+                    None => debug!("ignoring unsupported LS_COLORS key: {}", key),
+                },
+            }
+        }
+        theme
+    }
+
+    /// Applies the `[colors]` section of the configuration file.
+    ///
+    /// Missing keys keep their current colors; values that are not SGR
+    /// parameters (numbers separated by `;`) are reported as warnings and
+    /// skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The parsed configuration file (see
+    ///   [`load_config_file`](crate::config::load_config_file))
+    pub fn apply_config(&mut self, file: &toml::Table) {
+        let Some(toml::Value::Table(section)) = file.get("colors") else {
+            return;
+        };
+
+        let color = |key: &str, current: &mut String| match section.get(key) {
+            None => {}
+            Some(toml::Value::String(sgr)) if is_sgr(sgr) => *current = sgr.clone(),
+            Some(value) => warn!("ignoring invalid colors.{} {} in the configuration file", key, value),
+        };
+        color("fifo", &mut self.fifo);
+        color("socket", &mut self.socket);
+        color("block-device", &mut self.block_device);
+        color("char-device", &mut self.char_device);
+    }
+
+    /// Returns the `LS_COLORS` color of an entry, chosen as `ls` does.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The name of the entry
+    /// * `file_type` - The file type as returned by `get_file_type`
+    /// * `broken` - Whether the entry is a symlink to a missing target
+    ///
+    /// # Returns
+    ///
+    /// The SGR parameters, empty for no color
+    fn ls_color(&self, file_name: &str, file_type: &str, broken: bool) -> &str {
+        match file_type {
+            "Directory" => &self.directory,
+            "Symlink" if broken => self.orphan.as_ref().unwrap_or(&self.symlink),
+            "Symlink" => &self.symlink,
+            "FIFO" => &self.fifo,
+            "Socket" => &self.socket,
+            "Block Device" => &self.block_device,
+            "Character Device" => &self.char_device,
+            "Executable" => &self.executable,
+            _ => {
+                let name = file_name.to_lowercase();
+                self.suffixes
+                    .iter()
+                    .rev()
+                    .find(|(suffix, _)| name.ends_with(suffix.as_str()))
+                    .map_or(&self.file, |(_, sgr)| sgr)
+            }
+        }
+    }
+}
+
+/// Checks whether a string is a list of SGR parameters such as `01;35`.
+fn is_sgr(value: &str) -> bool {
+    value.split(';').all(|param| param.parse::<u8>().is_ok())
+}
+
+/// Process-wide theme, set once by [`init_theme`]
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Returns the theme in use, the default if [`init_theme`] was not called.
+fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+/// Sets the theme used for all colored file names.
+///
+/// Like the color mode, the theme applies to the whole run, so the
+/// name-coloring functions need no configuration passed in.
+///
+/// # Arguments
+///
+/// * `file` - The parsed configuration file (see
+///   [`load_config_file`](crate::config::load_config_file))
+pub fn init_theme(file: &toml::Table) {
+    let mut theme = std::env::var("LS_COLORS")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map_or_else(Theme::default, |value| Theme::from_ls_colors(&value));
+    theme.apply_config(file);
+    let _ = THEME.set(theme);
+}
+
+/// Sizes at which the Size column changes color.
+///
+/// The defaults suit general use; the `[size_colors]` section of the
+/// configuration file moves them, e.g. for a photo archive where only files
+/// over 100MB are worth flagging:
+///
+/// ```toml
+/// [size_colors]
+/// medium = "10MB"
+/// large = "100MB"
+/// huge = "1GB"
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeThresholds {
+    /// Smallest size shown in yellow
+    pub medium: u64,
+    /// Smallest size shown in magenta
+    pub large: u64,
+    /// Smallest size shown in bold red
+    pub huge: u64,
+}
+
+impl Default for SizeThresholds {
+    fn default() -> Self {
+        Self {
+            medium: 1024 * 1024,
+            large: 100 * 1024 * 1024,
+            huge: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+impl SizeThresholds {
+    /// Reads the `[size_colors]` section of the configuration file.
+    ///
+    /// Each of `medium`, `large`, and `huge` is a size such as "5MB" or a
+    /// number of bytes; missing keys keep their defaults. Invalid values, and
+    /// thresholds that are not in increasing order, are reported as warnings
+    /// and replaced with the defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The parsed configuration file (see
+    ///   [`load_config_file`](crate::config::load_config_file))
+    ///
+    /// # Returns
+    ///
+    /// The configured thresholds
+    pub fn from_config(file: &toml::Table) -> Self {
+        let defaults = Self::default();
+        let Some(toml::Value::Table(section)) = file.get("size_colors") else {
+            return defaults;
+        };
+
+        let threshold = |key: &str, default: u64| match section.get(key) {
+            None => default,
+            Some(toml::Value::Integer(bytes)) if *bytes >= 0 => *bytes as u64,
+            Some(toml::Value::String(size)) => parse_size(size).unwrap_or_else(|| {
+                warn!("ignoring invalid size_colors.{} '{}' in the configuration file", key, size);
+                default
+            }),
+            Some(value) => {
+                warn!("ignoring invalid size_colors.{} {} in the configuration file", key, value);
+                default
+            }
+        };
+        let thresholds = Self {
+            medium: threshold("medium", defaults.medium),
+            large: threshold("large", defaults.large),
+            huge: threshold("huge", defaults.huge),
+        };
+
+        if thresholds.medium <= thresholds.large && thresholds.large <= thresholds.huge {
+            thresholds
+        } else {
+            warn!("ignoring size_colors in the configuration file: medium, large, and huge must increase");
+            defaults
+        }
     }
 }
 
@@ -46,6 +454,7 @@ pub fn get_colored_name(file_name: &str, metadata: &fs::Metadata) -> String {
 ///
 /// * `size_str` - The formatted size string (e.g., "1.5K")
 /// * `size_bytes` - The actual size in bytes for comparison
+/// * `thresholds` - The sizes at which the color changes
 ///
 /// # Returns
 ///
@@ -53,27 +462,20 @@ pub fn get_colored_name(file_name: &str, metadata: &fs::Metadata) -> String {
 ///
 /// # Color Scheme
 ///
+/// With the default thresholds:
+///
 /// - Green: < 1MB (small files)
 /// - Yellow: 1MB - 100MB (medium files)
 /// - Magenta: 100MB - 1GB (large files)
 /// - Red (bold): > 1GB (very large files)
-pub fn get_colored_size(size_str: &str, size_bytes: u64) -> String {
-    // Color coding for file sizes:
-    // Green: < 1MB (small files)
-    // Yellow: 1MB - 100MB (medium files)
-    // Magenta: > 100MB (large files)
-    // Red: > 1GB (very large files)
-    if size_bytes >= 1024 * 1024 * 1024 {
-        // >= 1GB - Red
+pub fn get_colored_size(size_str: &str, size_bytes: u64, thresholds: &SizeThresholds) -> String {
+    if size_bytes >= thresholds.huge {
         format!("{}", size_str.red().bold())
-    } else if size_bytes >= 100 * 1024 * 1024 {
-        // >= 100MB - Magenta
+    } else if size_bytes >= thresholds.large {
         format!("{}", size_str.magenta())
-    } else if size_bytes >= 1024 * 1024 {
-        // >= 1MB - Yellow
+    } else if size_bytes >= thresholds.medium {
         format!("{}", size_str.yellow())
     } else {
-        // < 1MB - Green
         format!("{}", size_str.green())
     }
 }
@@ -100,6 +502,135 @@ pub fn get_colored_size(size_str: &str, size_bytes: u64) -> String {
 /// - VS Code terminal: Full support
 /// - Other terminals: Graceful fallback (sequences ignored)
 pub fn make_clickable_link(_file_name: &str, full_path: &Path, colored_name: &str) -> String {
+    hyperlink(
+        &format!("file://{}{}", link_host(), encoded_absolute_path(full_path)),
+        colored_name,
+    )
+}
+
+/// URL scheme of directory links that re-list the directory with `fls open`
+pub const LISTING_SCHEME: &str = "fls";
+
+/// Creates a terminal hyperlink that asks the terminal to re-list a directory.
+///
+/// The link uses the `fls://` scheme instead of `file://`, so a terminal
+/// configured to hand `fls://` URLs to `fls open` lists the directory in place
+/// instead of opening a file manager.
+///
+/// # Arguments
+///
+/// * `full_path` - The full path to the directory
+/// * `colored_name` - The colored display text for the link
+///
+/// # Returns
+///
+/// A string containing OSC 8 escape sequences that create a clickable hyperlink
+pub fn make_listing_link(full_path: &Path, colored_name: &str) -> String {
+    hyperlink(
+        &format!("{}://{}{}", LISTING_SCHEME, link_host(), encoded_absolute_path(full_path)),
+        colored_name,
+    )
+}
+
+/// Host of `fls://` links that carry a permission explanation instead of a path
+const EXPLANATION_HOST: &str = "explain";
+
+/// Characters escaped in explanation URLs; spaces become `+` and common
+/// punctuation stays readable, since terminals show the URL on hover
+const EXPLANATION_ESCAPES: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b',')
+    .remove(b';')
+    .remove(b':')
+    .remove(b'(')
+    .remove(b')')
+    .remove(b'\'');
+
+/// Creates a terminal hyperlink whose URL carries an explanation.
+///
+/// Terminals show a link's URL on hover, so the explanation works as a
+/// tooltip. Clicking it in a terminal that hands `fls://` URLs to `fls open`
+/// prints the explanation.
+///
+/// # Arguments
+///
+/// * `explanation` - The text to carry, such as a permission explanation
+/// * `text` - The display text for the link
+///
+/// # Returns
+///
+/// A string containing OSC 8 escape sequences that create a hyperlink
+pub fn make_explanation_link(explanation: &str, text: &str) -> String {
+    let encoded: Vec<String> = explanation
+        .split(' ')
+        .map(|word| percent_encode(word.as_bytes(), EXPLANATION_ESCAPES).to_string())
+        .collect();
+    hyperlink(
+        &format!("{}://{}?{}", LISTING_SCHEME, EXPLANATION_HOST, encoded.join("+")),
+        text,
+    )
+}
+
+/// Extracts the explanation from a link created by `make_explanation_link`.
+///
+/// # Arguments
+///
+/// * `url` - The URL of a clicked link
+///
+/// # Returns
+///
+/// The decoded explanation, or `None` if the URL is not an explanation link
+pub fn explanation_from_url(url: &str) -> Option<String> {
+    let rest = url.strip_prefix(LISTING_SCHEME)?.strip_prefix("://")?;
+    let query = rest.strip_prefix(EXPLANATION_HOST)?.strip_prefix('?')?;
+    let words: Vec<String> = query
+        .split('+')
+        .map(|word| percent_decode_str(word).decode_utf8_lossy().to_string())
+        .collect();
+    Some(words.join(" "))
+}
+
+/// Characters escaped in the host part of URLs; hostnames keep their dots and dashes
+const HOST_ESCAPES: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_');
+
+/// Returns the host part of hyperlink URLs.
+///
+/// Over SSH, links name the machine the files are on (`file://hostname/path`,
+/// as the OSC 8 spec recommends), so the local terminal can tell they are
+/// remote and hand them to an sftp handler. Local sessions keep the plain
+/// `file:///path` form. The hostname is looked up once per run.
+fn link_host() -> &'static str {
+    static HOST: OnceLock<String> = OnceLock::new();
+
+    HOST.get_or_init(|| {
+        let over_ssh = ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+            .iter()
+            .any(|name| std::env::var_os(name).is_some_and(|value| !value.is_empty()));
+        if !over_ssh {
+            return String::new();
+        }
+        hostname()
+            .map(|host| percent_encode(host.as_bytes(), HOST_ESCAPES).to_string())
+            .unwrap_or_default()
+    })
+}
+
+/// Reads the machine's hostname.
+fn hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    // SAFETY: the buffer is valid for writes of its full length
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if result != 0 {
+        return None;
+    }
+    let length = buffer.iter().position(|&byte| byte == 0).unwrap_or(buffer.len());
+    let host = String::from_utf8_lossy(&buffer[..length]).to_string();
+    (!host.is_empty()).then_some(host)
+}
+
+/// Makes a path absolute and percent-encodes it for use in a URL.
+fn encoded_absolute_path(full_path: &Path) -> String {
     // Convert path to absolute path if needed
     let absolute_path = if full_path.is_absolute() {
         full_path.to_path_buf()
@@ -108,10 +639,10 @@ pub fn make_clickable_link(_file_name: &str, full_path: &Path, colored_name: &st
             .unwrap_or_default()
             .join(full_path)
     };
-    
-    // Create file:// URL with percent encoding for special characters
+
+    // Percent encoding for special characters
     let url_path = absolute_path.to_string_lossy();
-    let encoded_path: String = url_path
+    url_path
         .chars()
         .map(|c| {
             if c == '/' || c == ':' {
@@ -120,15 +651,36 @@ pub fn make_clickable_link(_file_name: &str, full_path: &Path, colored_name: &st
                 percent_encode(c.to_string().as_bytes(), NON_ALPHANUMERIC).to_string()
             }
         })
-        .collect();
-    
-    let file_url = format!("file://{}", encoded_path);
-    
+        .collect()
+}
+
+/// Wraps text in an OSC 8 hyperlink to `url`.
+fn hyperlink(url: &str, text: &str) -> String {
     // OSC 8 escape sequence: \x1b]8;;URL\x1b\\TEXT\x1b]8;;\x1b\\
-    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", file_url, colored_name)
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// Converts a `fls://` or `file://` URL back into a local path.
+///
+/// A host part, if present, is ignored.
+///
+/// # Arguments
+///
+/// * `url` - The URL of a clicked link
+///
+/// # Returns
+///
+/// The decoded path, or `None` if the URL uses another scheme or has no path
+pub fn path_from_url(url: &str) -> Option<PathBuf> {
+    let (scheme, rest) = url.split_once("://")?;
+    if scheme != LISTING_SCHEME && scheme != "file" {
+        return None;
+    }
+    let path = &rest[rest.find('/')?..];
+    Some(PathBuf::from(percent_decode_str(path).decode_utf8_lossy().to_string()))
 }
 
-/// Formats a file name with color and optional interactive hyperlink using FileInfo.
+/// Formats a file name with color using FileInfo.
 ///
 /// This function is specifically designed to work with FileInfo structs and provides
 /// consistent color formatting across different display modes.
@@ -137,27 +689,10 @@ pub fn make_clickable_link(_file_name: &str, full_path: &Path, colored_name: &st
 ///
 /// * `file_name` - The name of the file
 /// * `file_info` - The FileInfo struct containing file metadata
-/// * `interactive` - Whether to add interactive hyperlinks
 ///
 /// # Returns
 ///
-/// A formatted string with colors and optional hyperlinks
-pub fn format_with_color(file_name: &str, file_info: &FileInfo, interactive: bool) -> String {
-    let colored_name = if file_info.is_hidden() {
-        format!("{}", file_name.bright_black())
-    } else if file_info.is_directory() {
-        format!("{}", file_name.blue().bold())
-    } else if file_info.is_executable() {
-        format!("{}", file_name.green().bold())
-    } else {
-        file_name.to_string()
-    };
-
-    if interactive {
-        let current_dir = std::env::current_dir().unwrap_or_default();
-        let full_path = current_dir.join(file_name);
-        make_clickable_link(file_name, &full_path, &colored_name)
-    } else {
-        colored_name
-    }
+/// A formatted string with colors
+pub fn format_with_color(file_name: &str, file_info: &FileInfo) -> String {
+    colored_name(file_name, &file_info.file_type, file_info.broken_link)
 }
\ No newline at end of file
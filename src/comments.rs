@@ -0,0 +1,108 @@
+//! Per-entry comments for the `--comments` Comment column.
+//!
+//! A comment is attached to a file either directly, in the `user.fls.comment`
+//! extended attribute, or in a `.fls-notes.toml` file in the same directory
+//! that maps entry names to comments:
+//!
+//! ```toml
+//! "raw-2024.csv" = "Unfiltered export, do not edit"
+//! scripts = "Owned by the data team"
+//! ```
+//!
+//! The extended attribute wins when both are present, since it travels with
+//! the file when it is moved or copied with its attributes.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::{debug, warn};
+
+/// Extended attribute holding a file's comment
+pub const COMMENT_ATTRIBUTE: &str = "user.fls.comment";
+
+/// Sidecar file mapping entry names to comments
+pub const NOTES_FILE: &str = ".fls-notes.toml";
+
+/// Comments of the entries in one directory.
+#[derive(Default)]
+pub struct Notes {
+    /// Comments from the directory's notes file, by entry name
+    sidecar: HashMap<String, String>,
+}
+
+impl Notes {
+    /// Loads the notes file of a directory.
+    ///
+    /// A missing notes file means no sidecar comments; a malformed one is
+    /// reported as a warning and otherwise ignored. Values that are not
+    /// strings are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - The directory whose entries are being listed
+    ///
+    /// # Returns
+    ///
+    /// The directory's comments
+    pub fn load(directory: &Path) -> Self {
+        let path = directory.join(NOTES_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                debug!("no notes file {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+
+        let table = match contents.parse::<toml::Table>() {
+            Ok(table) => table,
+            Err(e) => {
+                warn!("ignoring malformed notes file {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+
+        let sidecar = table
+            .into_iter()
+            .filter_map(|(name, value)| match value {
+                toml::Value::String(comment) => Some((name, comment)),
+                _ => {
+                    warn!("ignoring non-string note for '{}' in {}", name, path.display());
+                    None
+                }
+            })
+            .collect();
+        Self { sidecar }
+    }
+
+    /// Returns the comment of an entry, from its extended attribute or the
+    /// notes file.
+    ///
+    /// Control characters are replaced with spaces, so a comment always fits
+    /// on one line and cannot inject terminal escape sequences.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The entry name, as used in the notes file
+    /// * `path` - Path to the entry
+    ///
+    /// # Returns
+    ///
+    /// The comment, or None if the entry has none
+    pub fn comment(&self, name: &str, path: &Path) -> Option<String> {
+        let attribute = xattr::get(path, COMMENT_ATTRIBUTE)
+            .ok()
+            .flatten()
+            .map(|value| String::from_utf8_lossy(&value).to_string());
+        attribute
+            .or_else(|| self.sidecar.get(name).cloned())
+            .map(|comment| {
+                comment
+                    .trim()
+                    .chars()
+                    .map(|c| if c.is_control() { ' ' } else { c })
+                    .collect()
+            })
+            .filter(|comment: &String| !comment.is_empty())
+    }
+}
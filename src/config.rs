@@ -4,10 +4,314 @@
 //! command-line options and their values, replacing the previous approach of passing
 //! multiple boolean parameters between functions.
 
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::colors::SizeThresholds;
+use crate::exec_column::ExecColumn;
+use crate::formatting::{DisplayLocale, DisplayTimeZone};
+use crate::icons::Icons;
+use crate::openers::Openers;
+
+/// Horizontal alignment of a table column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnAlignment {
+    Left,
+    Right,
+    Center,
+}
+
+/// Timestamp columns shown in table format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TimeColumns {
+    /// Last modification time (default)
+    #[default]
+    Modified,
+    /// Last access time
+    Accessed,
+    /// Last status (inode) change time
+    Changed,
+    /// Creation (birth) time, where the platform supports it
+    Created,
+    /// All of the above as separate columns
+    All,
+}
+
+/// Curated sets of table columns selected with `--preset`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColumnPreset {
+    /// Name, size, and modification time only
+    Minimal,
+    /// Permissions, ownership, and status change time, for access reviews
+    Security,
+    /// Type, extension, size, modification time, and age, for source trees
+    Dev,
+    /// Inode number, ownership, sizes, all timestamps, and content hashes
+    Forensics,
+}
+
+impl ColumnPreset {
+    /// Returns the columns the preset shows, by header name.
+    ///
+    /// Optional columns in the list (such as Hash) are turned on by the preset;
+    /// other optional columns still follow their own flags.
+    ///
+    /// # Returns
+    ///
+    /// The header names of the preset's columns
+    pub fn columns(self) -> &'static [&'static str] {
+        match self {
+            ColumnPreset::Minimal => &["Name", "Size", "Modified"],
+            ColumnPreset::Security => &[
+                "Name",
+                "Type",
+                "User Permission",
+                "Group Permission",
+                "Other Permission",
+                "Octal",
+                "User",
+                "Group",
+                "Changed",
+            ],
+            ColumnPreset::Dev => &["Name", "Type", "Ext", "Size", "Modified", "Age", "Items"],
+            ColumnPreset::Forensics => &[
+                "Name", "Inode", "Type", "Octal", "User", "Group", "Size", "Allocated", "Modified", "Accessed",
+                "Changed", "Created", "Hash",
+            ],
+        }
+    }
+
+    /// Returns the timestamp columns the preset shows unless `--times` is given.
+    ///
+    /// # Returns
+    ///
+    /// The preset's timestamp selection
+    pub fn times(self) -> TimeColumns {
+        match self {
+            ColumnPreset::Minimal | ColumnPreset::Dev => TimeColumns::Modified,
+            ColumnPreset::Security => TimeColumns::Changed,
+            ColumnPreset::Forensics => TimeColumns::All,
+        }
+    }
+
+    /// Checks whether the preset shows a column.
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - The column's header name
+    ///
+    /// # Returns
+    ///
+    /// `true` if the column is part of the preset
+    pub fn shows(self, column: &str) -> bool {
+        self.columns().contains(&column)
+    }
+}
+
+/// Order in which entries are listed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortBy {
+    /// Alphabetically by name (default)
+    Name,
+    /// By size, largest first
+    Size,
+    /// By modification time, newest first (also `age`)
+    #[value(alias = "age")]
+    Mtime,
+    /// Alphabetically by extension, then by name
+    Ext,
+    /// By creation (birth) time, newest first
+    Created,
+    /// By owning user name
+    User,
+    /// By owning group name
+    Group,
+    /// In the order the filesystem returns them, without sorting
+    None,
+}
+
+/// How entries with equal sort keys are ordered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Tiebreak {
+    /// Alphabetically by name (default)
+    #[default]
+    Name,
+    /// By modification time, newest first, then by name
+    Mtime,
+    /// In the order the filesystem returns them, which may differ between runs
+    None,
+}
+
+/// Key by which long-format entries are grouped under separator rows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum GroupBy {
+    /// By file extension (case-insensitive)
+    Ext,
+}
+
+/// Value visualized by the heat bar column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum HeatBy {
+    /// Recency of modification: the most recently modified sibling is hottest
+    Age,
+    /// Size on a logarithmic scale: the largest sibling is hottest
+    Size,
+}
+
+/// Totals shown in the `--summary-row` row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Aggregate {
+    /// Number of entries, in the Items column
+    Count,
+    /// Sum of the sizes, in the Size column
+    Size,
+    /// Sum of the allocated disk space (blocks), in the Allocated column
+    #[value(alias = "blocks")]
+    Allocated,
+    /// Number of entries of each type, in the Type column
+    Types,
+}
+
+/// Where directory hyperlinks in the interactive tree point to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DirLinks {
+    /// `file://` URLs, opened with the system's default application (default)
+    #[default]
+    File,
+    /// `fls://` URLs, which a configured terminal hands to `fls open` to re-list the directory
+    Fls,
+}
+
+/// Whose size and modification time a symlink shows in the table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LinkSizes {
+    /// The link itself (default)
+    #[default]
+    Link,
+    /// The file or directory the link points to
+    Target,
+}
+
+/// When to color the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorMode {
+    /// Follow `NO_COLOR`/`CLICOLOR`, else color only in a terminal (default)
+    #[default]
+    Auto,
+    /// Always color, even when output is redirected
+    Always,
+    /// Never color
+    Never,
+}
+
+/// Formats the colored output can be exported to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// HTML with styled spans reproducing the terminal colors
+    AnsiHtml,
+    /// SVG diagram of the tree (requires --tree)
+    Svg,
+}
+
+/// Delimited formats the long listing can be written in, with raw values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum DelimitedFormat {
+    /// Comma-separated values, quoted as in RFC 4180
+    Csv,
+    /// Tab-separated values
+    Tsv,
+}
+
+/// Returns the path of the configuration file, `$XDG_CONFIG_HOME/fls/config.toml`.
+///
+/// # Returns
+///
+/// The path (which may not exist), or None if neither `XDG_CONFIG_HOME` nor
+/// `HOME` is set
+pub fn config_file() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("fls").join("config.toml"))
+}
+
+/// Reads and parses the configuration file.
+///
+/// A missing configuration file is the same as an empty one. The file is read
+/// before the command line is parsed, so before logging is set up; problems
+/// are therefore returned for the caller to report.
+///
+/// # Returns
+///
+/// The file's top-level table, empty if it is missing
+///
+/// # Errors
+///
+/// Returns a message naming the file if it cannot be read or is malformed
+pub fn load_config_file() -> Result<toml::Table, String> {
+    let Some(path) = config_file() else {
+        return Ok(toml::Table::new());
+    };
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(toml::Table::new()),
+        Err(e) => return Err(format!("cannot read configuration file {}: {}", path.display(), e)),
+    };
+
+    contents
+        .parse::<toml::Table>()
+        .map_err(|e| format!("malformed configuration file {}: {}", path.display(), e))
+}
+
+/// Turns the `[defaults]` section of the configuration file into command-line
+/// options.
+///
+/// Each key is the long name of an option: `true` adds the flag (`false`
+/// leaves it out), a string or number becomes its value, and an array repeats
+/// the option once per element. For example `sort = "size"`, `long = true`,
+/// and `exclude = ["*.o", "target"]` become `--sort=size --long
+/// --exclude=*.o --exclude=target`.
+///
+/// # Arguments
+///
+/// * `table` - The configuration file's top-level table (see [`load_config_file`])
+///
+/// # Returns
+///
+/// The options, to be parsed before those of the command line
+pub fn default_arguments(table: &toml::Table) -> Vec<String> {
+    let Some(defaults) = table.get("defaults").and_then(toml::Value::as_table) else {
+        return Vec::new();
+    };
+
+    let option = |key: &str, value: &toml::Value| match value {
+        // Strings without their TOML quotes
+        toml::Value::String(value) => format!("--{}={}", key, value),
+        value => format!("--{}={}", key, value),
+    };
+    let mut arguments = Vec::new();
+    for (key, value) in defaults {
+        match value {
+            toml::Value::Boolean(true) => arguments.push(format!("--{}", key)),
+            toml::Value::Boolean(false) => {}
+            toml::Value::Array(values) => arguments.extend(values.iter().map(|value| option(key, value))),
+            value => arguments.push(option(key, value)),
+        }
+    }
+    arguments
+}
+
+/// Deepest level any recursive mode descends to, to prevent runaway recursion
+pub const MAX_DEPTH: usize = 20;
+
 /// Configuration structure that holds all command-line options and their values.
 ///
 /// This struct provides a clean interface for passing configuration between modules
 /// and ensures type safety for all options.
+#[derive(Clone)]
 pub struct Config {
     /// The directory path to list (default: current directory)
     pub path: String,
@@ -15,32 +319,176 @@ pub struct Config {
     pub long_format: bool,
     /// Whether to show hidden files (files starting with '.')
     pub show_hidden: bool,
+    /// Whether to also list the `.` and `..` pseudo-entries (implies `show_hidden`)
+    pub dot_entries: bool,
     /// Whether to enable clickable file names using terminal hyperlinks
     pub interactive: bool,
+    /// Where directory hyperlinks in the interactive tree point to
+    pub dir_links: DirLinks,
+    /// Whether to write the listing as a JSON array of raw entry data
+    pub json: bool,
+    /// Whether JSON entries include their 1-based position in the sort order
+    pub rank: bool,
+    /// Whether the configuration file was ignored (--no-config)
+    pub no_config: bool,
+    /// Options taken from the `[defaults]` section of the configuration file
+    pub config_defaults: Vec<String>,
+    /// Commands from the configuration file that `fls open` runs for files, by extension
+    pub openers: Openers,
+    /// Sizes at which the Size column turns yellow, magenta, and red
+    pub size_thresholds: SizeThresholds,
+    /// Whether to list subdirectories recursively, one section per directory
+    pub recursive: bool,
+    /// Whether to print a `path:` header above each section of a multi-section listing
+    pub section_headers: bool,
     /// Whether to display files in a tree-like structure
     pub tree: bool,
-    /// Maximum depth for tree traversal (None = unlimited)
-    pub tree_depth: Option<usize>,
+    /// Maximum traversal depth of recursive modes (None = up to [`MAX_DEPTH`])
+    pub depth: Option<usize>,
+    /// In the tree, list subdirectories first and show at most this many files per directory
+    pub collapse_files: Option<usize>,
+    /// Whether the tree shows the number of subdirectories and files after each directory name
+    pub dir_counts: bool,
+    /// Maximum width of the Name column in table format before wrapping (None = no wrapping)
+    pub wrap_width: Option<usize>,
+    /// Per-column alignment overrides as (column header, alignment) pairs
+    pub column_alignments: Vec<(String, ColumnAlignment)>,
+    /// Whether to append a totals row (entry count, cumulative size) to the table
+    pub summary_row: bool,
+    /// Totals shown in the summary row
+    pub aggregates: Vec<Aggregate>,
+    /// Whether to draw the table without separator lines between rows
+    pub compact: bool,
+    /// Number of lines of small text files to preview (table column or below the name)
+    pub preview: Option<usize>,
+    /// Layout width for table output from `--width` or `COLUMNS` (None = unconstrained)
+    pub width: Option<usize>,
+    /// Width the simple view packs names into columns for, or None for one name per line
+    pub grid_width: Option<usize>,
+    /// Whether to show recursive content sizes for directories in table format
+    pub recursive_sizes: bool,
+    /// Whether to show recursive inode counts for directories in table format
+    pub inodes: bool,
+    /// Whether to show the Inode (inode number) column in table format
+    pub inode_numbers: bool,
+    /// Column preset whose columns replace the default table columns
+    pub preset: Option<ColumnPreset>,
+    /// Whether to show content hashes of files and directory trees in table format
+    pub dir_hash: bool,
+    /// Columns filled by running a shell command per entry, shown after the built-in columns
+    pub exec_columns: Vec<ExecColumn>,
+    /// How long each `--exec-column` command may run before it is killed
+    pub exec_timeout: Duration,
+    /// Whether to show the Comment column (from `user.fls.comment` or `.fls-notes.toml`) in table format
+    pub comments: bool,
+    /// Whether to leave the User/Group cells blank when they match the invoking user
+    pub smart_owner: bool,
+    /// Whether to highlight permission cells that deviate from the expected modes
+    pub highlight_permissions: bool,
+    /// Expected permission bits of regular files (executables may add execute where readable)
+    pub expected_file_mode: u32,
+    /// Expected permission bits of directories
+    pub expected_dir_mode: u32,
+    /// Table columns hidden with `--hide`, by header name
+    pub hidden_columns: Vec<String>,
+    /// Which timestamp columns to show in table format
+    pub times: TimeColumns,
+    /// Whether to show exact byte sizes with thousands separators instead of rounded sizes
+    pub exact_bytes: bool,
+    /// Whether to show the size unit in a separate Unit column next to the Size number
+    pub size_split: bool,
+    /// Whose size and modification time symlinks show in the table
+    pub link_sizes: LinkSizes,
+    /// Whether to write diff-friendly snapshots: tab-separated, uncolored, UTC, locale-independent
+    pub deterministic: bool,
+    /// Delimited format the rows are written in with raw values (`--format`), instead of a drawn table
+    pub format: Option<DelimitedFormat>,
+    /// Whether to write long listings as traditional `ls -l` lines instead of a table (`--classic`)
+    pub classic: bool,
+    /// Whether to shade every second table row
+    pub stripe: bool,
+    /// Whether to show the Ext (file extension) column in table format
+    pub show_ext: bool,
+    /// Icons written before names (--icons), with the configuration file's overrides
+    pub icons: Option<Icons>,
+    /// Key by which table rows are grouped under separator rows
+    pub group_by: Option<GroupBy>,
+    /// Whether to show allocated sizes next to apparent sizes and highlight large differences
+    pub disk_usage_delta: bool,
+    /// Whether to show the Age column (time since modification) in table format
+    pub show_age: bool,
+    /// Value shown as a heat bar relative to the other entries (None = no Heat column)
+    pub heat: Option<HeatBy>,
+    /// Badge entries modified within this duration (None = no badges)
+    pub highlight_new: Option<Duration>,
+    /// Whether to mark entries whose names differ from a sibling's only by case
+    pub check_case_collisions: bool,
+    /// Whether to mark names that cannot be used on Windows or break tools (control characters, invalid UTF-8)
+    pub check_names: bool,
+    /// Whether to mark entries whose user or group ID has no account
+    pub check_owners: bool,
+    /// Order in which entries are listed
+    pub sort: SortBy,
+    /// Whether to reverse the sort order
+    pub reverse: bool,
+    /// Order of entries whose sort keys are equal
+    pub tiebreak: Tiebreak,
+    /// Table column (canonical header name) that orders the table rows instead of `sort`
+    pub sort_column: Option<String>,
+    /// Time zone used to render timestamps (local, UTC, or a named zone)
+    pub time_zone: DisplayTimeZone,
+    /// Locales used for month names and digit grouping
+    pub locale: DisplayLocale,
+    /// Whether to hide entries matched by `.gitignore`, `.ignore`, and git excludes
+    pub git_ignore: bool,
+    /// Only list entries whose names match one of these glob patterns (empty = all)
+    pub name_globs: Vec<glob::Pattern>,
+    /// Only list entries whose names match this regular expression
+    pub name_regex: Option<regex::Regex>,
+    /// Only list entries modified within this duration (None = no limit)
+    pub newer_than: Option<Duration>,
+    /// Only list entries last modified at least this long ago (None = no limit)
+    pub older_than: Option<Duration>,
+    /// Whether to list only non-directory entries with an execute bit set
+    pub executables_only: bool,
+    /// Whether to list only directories (-d), like `tree -d`
+    pub dirs_only: bool,
+    /// Only list files whose contents match this pattern
+    pub contains: Option<regex::bytes::Regex>,
+    /// Only list entries owned by this user (name or numeric ID)
+    pub owner_user: Option<String>,
+    /// Only list entries owned by this group (name or numeric ID)
+    pub owner_group: Option<String>,
+    /// Entries whose names match one of these glob patterns are hidden
+    pub exclude_patterns: Vec<glob::Pattern>,
+    /// Whether to hide entries matching the backup patterns (-B)
+    pub ignore_backups: bool,
+    /// Name glob patterns treated as backup or temporary files
+    pub backup_patterns: Vec<glob::Pattern>,
+    /// Whether to follow a symlink given as the path argument in long format (-H)
+    pub dereference_args: bool,
+    /// Whether to compare against the previous run on the same path and mark changes
+    pub changed: bool,
+    /// Whether to stream entries in directory order without sorting, caches, or worker threads
+    pub low_memory: bool,
+    /// Whether to keep the tree on screen and update it as the filesystem changes
+    pub watch: bool,
+    /// Format to convert the colored output to, instead of printing it for a terminal
+    pub export: Option<ExportFormat>,
+    /// Whether SVG tree export adds bars proportional to each entry's size
+    pub scale_by_size: bool,
 }
 
 impl Config {
-    /// Creates a new Config instance from parsed command-line arguments.
+    /// Returns how many levels below the listed directory recursive modes descend.
     ///
-    /// # Arguments
-    ///
-    /// * `matches` - The parsed command-line arguments from clap
+    /// Every recursive traversal (tree view, live tree, SVG export, `-R`) uses this
+    /// limit, so `-L/--depth` means the same thing in all of them.
     ///
     /// # Returns
     ///
-    /// A new Config instance with values extracted from the command-line arguments.
-    pub fn from_matches(matches: clap::ArgMatches) -> Self {
-        Self {
-            path: matches.get_one::<String>("path").unwrap().clone(),
-            long_format: matches.get_flag("long"),
-            show_hidden: matches.get_flag("all"),
-            interactive: matches.get_flag("interactive"),
-            tree: matches.get_flag("tree"),
-            tree_depth: matches.get_one::<u8>("depth").map(|&d| d as usize),
-        }
+    /// The `--depth` value, capped at [`MAX_DEPTH`]
+    pub fn max_depth(&self) -> usize {
+        self.depth.unwrap_or(MAX_DEPTH).min(MAX_DEPTH)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,142 @@
+//! Recursive directory size and inode computation.
+//!
+//! This module sums the sizes of everything below a directory so listings can
+//! show the real space a directory accounts for instead of the size of the
+//! directory entry itself, and counts the inodes a tree uses for filesystems
+//! that run out of inodes before bytes. Multiple directories are measured in
+//! parallel.
+//!
+//! Directory sizes are cached for the rest of the run, so the tree view, which
+//! shows the size of every directory at every level, walks each subtree once.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::parallel::map_ordered;
+use crate::spinner::Spinner;
+use crate::virtual_fs::is_virtual;
+
+/// Returns the process-wide cache of the recursive size of each measured directory.
+fn size_cache() -> &'static Mutex<HashMap<PathBuf, u64>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, u64>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Computes the total size in bytes of all files below a directory.
+///
+/// Symlinks are not followed, so linked trees are not counted twice and
+/// symlink loops cannot cause infinite recursion. Unreadable entries are skipped,
+/// and so are virtual filesystems such as `/proc`, whose sizes are meaningless.
+/// The sizes of the directory and of every directory below it are cached.
+///
+/// # Arguments
+///
+/// * `path` - The directory to measure
+///
+/// # Returns
+///
+/// The cumulative size in bytes of all non-directory entries in the tree
+pub fn recursive_size(path: &Path) -> u64 {
+    if let Some(&size) = size_cache().lock().unwrap_or_else(|e| e.into_inner()).get(path) {
+        return size;
+    }
+    if is_virtual(path) {
+        return 0;
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+
+    let size = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.path().symlink_metadata() {
+            Ok(metadata) if metadata.is_dir() => recursive_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum();
+    size_cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(path.to_path_buf(), size);
+    size
+}
+
+/// Computes recursive sizes for several directories in parallel.
+///
+/// Work is shared by a pool of threads (one per CPU core), so the wall time
+/// approaches that of the largest directory rather than the sum of a batch. A
+/// spinner is shown on stderr while the computation runs.
+///
+/// # Arguments
+///
+/// * `paths` - The directories to measure
+///
+/// # Returns
+///
+/// The recursive size of each directory, in the same order as `paths`
+pub fn recursive_sizes(paths: &[PathBuf]) -> Vec<u64> {
+    if paths.is_empty() {
+        return Vec::new();
+    }
+
+    let _spinner = Spinner::start("Computing directory sizes...");
+
+    map_ordered(paths, |path| recursive_size(path))
+}
+
+/// Counts the distinct inodes used by a directory tree, including the directory itself.
+///
+/// Hard-linked files are counted once, as with `du --inodes`. Symlinks are
+/// counted but not followed. Virtual filesystems such as `/proc` are counted as
+/// their mount point only, since they are generated on access.
+///
+/// # Arguments
+///
+/// * `path` - The directory to measure
+///
+/// # Returns
+///
+/// The number of distinct inodes in the tree
+pub fn recursive_inodes(path: &Path) -> u64 {
+    let mut seen = HashSet::new();
+    collect_inodes(path, &mut seen);
+    seen.len() as u64
+}
+
+fn collect_inodes(path: &Path, seen: &mut HashSet<(u64, u64)>) {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return;
+    };
+    if !seen.insert((metadata.dev(), metadata.ino())) || !metadata.is_dir() || is_virtual(path) {
+        return;
+    }
+
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            collect_inodes(&entry.path(), seen);
+        }
+    }
+}
+
+/// Counts the inodes of several directory trees in parallel.
+///
+/// # Arguments
+///
+/// * `paths` - The directories to measure
+///
+/// # Returns
+///
+/// The inode count of each directory tree, in the same order as `paths`
+pub fn recursive_inode_counts(paths: &[PathBuf]) -> Vec<u64> {
+    if paths.is_empty() {
+        return Vec::new();
+    }
+
+    let _spinner = Spinner::start("Counting inodes...");
+
+    map_ordered(paths, |path| recursive_inodes(path))
+}
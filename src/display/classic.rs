@@ -0,0 +1,196 @@
+//! Traditional `ls -l` output implementation.
+//!
+//! This module provides the `--classic` format: one line per entry laid out
+//! like POSIX `ls -l`, for users and scripts that expect the conventional
+//! layout rather than a table:
+//!
+//! ```text
+//! drwxr-xr-x  3 user group  4096 Jun  8 14:30 src
+//! ```
+//!
+//! Link counts and sizes are right-aligned, owners left-aligned, and names
+//! keep their colors, like `ls -l --color`.
+
+use colored::*;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::checks::check_badges;
+use crate::colors::{get_colored_name, link_suffix, make_clickable_link};
+use crate::config::{Config, TimeColumns};
+use crate::file_info::{changed_time, get_file_type, group_name, link_target, user_name};
+use crate::filter;
+use crate::formatting::{format_classic_time, format_symbolic_permissions};
+use crate::icons::icon_prefix;
+
+/// Bytes in the blocks of the `total` line, as in GNU `ls`
+const TOTAL_BLOCK_SIZE: u64 = 1024;
+
+/// Bytes in the blocks `st_blocks` counts
+const STAT_BLOCK_SIZE: u64 = 512;
+
+/// The columns of one line, before they are padded to a common width.
+struct ClassicLine {
+    permissions: String,
+    links: String,
+    user: String,
+    group: String,
+    size: String,
+    time: String,
+    name: String,
+}
+
+/// Displays directory entries as `ls -l` lines, after a `total` line with
+/// the space they use in 1K blocks.
+///
+/// Entries are filtered and ordered like in the other formats, and their own
+/// metadata is shown, so symlinks are listed as links.
+///
+/// # Arguments
+///
+/// * `entries` - Directory entries to display (already sorted)
+/// * `config` - Configuration specifying display options
+/// * `out` - Destination for the listing
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn display(
+    entries: &[Result<fs::DirEntry, std::io::Error>],
+    config: &Config,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let dot_entries = super::dot_entries(config)
+        .into_iter()
+        .map(|(name, path, metadata)| (name.to_string(), path, metadata));
+    let entries = entries
+        .iter()
+        .filter_map(|entry| entry.as_ref().ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            match entry.metadata() {
+                Ok(metadata) => Some((name, entry.path(), metadata)),
+                Err(e) => {
+                    warn!("cannot read metadata of {}: {}", name, e);
+                    None
+                }
+            }
+        })
+        .filter(|(name, path, metadata)| {
+            filter::matches(name, metadata, config) && filter::matches_contents(path, metadata, config)
+        });
+    let listed: Vec<(String, PathBuf, fs::Metadata)> = dot_entries.chain(entries).collect();
+
+    let blocks: u64 = listed.iter().map(|(_, _, metadata)| metadata.blocks()).sum();
+    writeln!(out, "total {}", (blocks * STAT_BLOCK_SIZE).div_ceil(TOTAL_BLOCK_SIZE))?;
+    write_lines(&listed, config, out)
+}
+
+/// Displays path arguments as `ls -l` lines, named as given on the command line.
+///
+/// The paths are not filtered, since they were named explicitly, and there is
+/// no `total` line, as in `ls -l FILE...`.
+///
+/// # Arguments
+///
+/// * `paths` - The paths to display with their own metadata (from
+///   `symlink_metadata`), already sorted
+/// * `config` - Configuration specifying display options
+/// * `out` - Destination for the listing
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn display_paths(paths: Vec<(PathBuf, fs::Metadata)>, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let listed: Vec<(String, PathBuf, fs::Metadata)> = paths
+        .into_iter()
+        .map(|(path, metadata)| (path.display().to_string(), path, metadata))
+        .collect();
+    write_lines(&listed, config, out)
+}
+
+/// Writes the listed entries one per line, with each column padded to its widest value.
+fn write_lines(listed: &[(String, PathBuf, fs::Metadata)], config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let lines: Vec<ClassicLine> = listed
+        .iter()
+        .map(|(name, path, metadata)| classic_line(name, path, metadata, config))
+        .collect();
+
+    let width = |column: fn(&ClassicLine) -> &str| lines.iter().map(|line| column(line).chars().count()).max().unwrap_or(0);
+    let links_width = width(|line| &line.links);
+    let user_width = width(|line| &line.user);
+    let group_width = width(|line| &line.group);
+    let size_width = width(|line| &line.size);
+
+    for line in &lines {
+        writeln!(
+            out,
+            "{} {:>links_width$} {:<user_width$} {:<group_width$} {:>size_width$} {} {}",
+            line.permissions, line.links, line.user, line.group, line.size, line.time, line.name,
+        )?;
+    }
+    Ok(())
+}
+
+/// Builds the columns of one entry's line.
+///
+/// The time column shows the modification time, or the time selected with
+/// `--times` (like `ls -lu` and `ls -lc`).
+fn classic_line(name: &str, path: &Path, metadata: &fs::Metadata, config: &Config) -> ClassicLine {
+    let time = match config.times {
+        TimeColumns::Accessed => metadata.accessed().ok(),
+        TimeColumns::Changed => changed_time(metadata),
+        TimeColumns::Created => metadata.created().ok(),
+        TimeColumns::Modified | TimeColumns::All => metadata.modified().ok(),
+    };
+    ClassicLine {
+        permissions: format_symbolic_permissions(metadata),
+        links: metadata.nlink().to_string(),
+        user: user_name(metadata.uid()),
+        group: group_name(metadata.gid()),
+        size: size_column(metadata),
+        time: format_classic_time(time, config.time_zone, config.locale.time),
+        name: name_column(name, path, metadata, config),
+    }
+}
+
+/// Returns an entry's size in bytes, or for a device its major and minor
+/// numbers (e.g. `8, 1`), as `ls -l` shows them.
+fn size_column(metadata: &fs::Metadata) -> String {
+    let file_type = metadata.file_type();
+    if file_type.is_char_device() || file_type.is_block_device() {
+        let device = metadata.rdev() as libc::dev_t;
+        format!("{}, {}", libc::major(device), libc::minor(device))
+    } else {
+        metadata.len().to_string()
+    }
+}
+
+/// Returns an entry's colored name, followed for a symlink by `-> target`
+/// and by the badges of failed name checks.
+fn name_column(name: &str, path: &Path, metadata: &fs::Metadata, config: &Config) -> String {
+    let colored_name = get_colored_name(name, path, metadata);
+    let link = link_target(path, metadata);
+    let broken = link.as_ref().is_some_and(|(_, broken)| *broken);
+
+    let mut column = icon_prefix(name, &get_file_type(metadata), broken, config);
+    if config.interactive {
+        column.push_str(&make_clickable_link(name, path, &colored_name));
+    } else {
+        column.push_str(&colored_name);
+    }
+    if let Some((target, broken)) = link {
+        column.push_str(&link_suffix(&target, broken));
+    }
+    // The `.` and `..` entries are named after other directories
+    if name != "." && name != ".." {
+        let badges = check_badges(path, config);
+        if !badges.is_empty() {
+            column.push_str(&badges.red().bold().to_string());
+        }
+    }
+    column
+}
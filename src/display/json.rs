@@ -0,0 +1,282 @@
+//! JSON output implementation.
+//!
+//! This module provides the `-j/--json` format: an array with one object per
+//! entry holding raw values instead of the formatted table cells, so the
+//! listing can be piped into `jq` or read by scripts. Sizes are in bytes,
+//! owners are given both as numeric IDs and names, permissions as the mode
+//! bits, and timestamps as ISO-8601 strings in the configured time zone.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::warn;
+
+use crate::checks::failed_checks;
+use crate::comments::Notes;
+use crate::config::Config;
+use crate::dir_hash::content_hashes;
+use crate::dir_size::recursive_sizes;
+use crate::exec_column;
+use crate::file_info::{allocated_size, changed_time, file_extension, get_file_type, group_name, user_name};
+use crate::filter;
+use crate::formatting::{format_iso8601, DisplayTimeZone};
+use crate::statx::extended_metadata;
+use crate::virtual_fs::is_virtual;
+
+/// One listed entry as serialized to JSON.
+#[derive(Serialize)]
+struct JsonEntry {
+    /// 1-based position in the listing's sort order, with `--rank`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rank: Option<usize>,
+    /// Entry name
+    name: String,
+    /// Path of the entry as reached from the listed directory
+    path: String,
+    /// File type, as in the table's Type column (e.g. "Directory", "Symlink")
+    #[serde(rename = "type")]
+    file_type: String,
+    /// Extension as written in the name, or None for directories and names without one
+    extension: Option<String>,
+    /// Apparent size in bytes
+    size: u64,
+    /// Disk space allocated in bytes
+    allocated: u64,
+    /// Size of the directory's contents in bytes, with `--recursive-sizes`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recursive_size: Option<u64>,
+    /// Permission bits including setuid, setgid, and sticky (e.g. 493 for 0755)
+    mode: u32,
+    /// Permission bits in octal notation (e.g. "755")
+    octal: String,
+    /// Owning user ID
+    uid: u32,
+    /// Owning user name, or the ID if it has no name
+    user: String,
+    /// Owning group ID
+    gid: u32,
+    /// Owning group name, or the ID if it has no name
+    group: String,
+    /// Inode number
+    inode: u64,
+    /// Number of hard links
+    links: u64,
+    /// Last modification time
+    modified: Option<String>,
+    /// Last access time
+    accessed: Option<String>,
+    /// Last status change time
+    changed: Option<String>,
+    /// Creation time, where the platform supports it
+    created: Option<String>,
+    /// ID of the mount the entry is on, where `statx` supports it (Linux 5.8+)
+    mount_id: Option<u64>,
+    /// Target of a symlink, or None for other entries
+    target: Option<String>,
+    /// Content hash, with `--dir-hash`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+    /// Comment from the entry's xattr or the notes file, with `--comments`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+    /// Cells of the `--exec-column` columns, by column name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    exec: BTreeMap<String, String>,
+    /// Failed name checks, with `--check-case-collisions` and `--check-names`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    problems: Vec<&'static str>,
+}
+
+/// Displays directory entries as a JSON array.
+///
+/// Entries are filtered and ordered like in the other formats. Recursive
+/// sizes, content hashes, comments, and exec column cells are only computed
+/// when their flags (`--recursive-sizes`, `--dir-hash`, `--comments`,
+/// `--exec-column`) are given.
+///
+/// # Arguments
+///
+/// * `entries` - Directory entries to display (already sorted)
+/// * `config` - Configuration specifying display options
+/// * `out` - Destination for the JSON document
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn display(
+    entries: &[Result<fs::DirEntry, std::io::Error>],
+    config: &Config,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    write_entries(&directory_entries(entries, config), config, out)
+}
+
+/// Pairs readable directory entries with their names and metadata and keeps
+/// those passing the filters, after the `.` and `..` entries of `--all-all`.
+pub(super) fn directory_entries(
+    entries: &[Result<fs::DirEntry, std::io::Error>],
+    config: &Config,
+) -> Vec<(String, PathBuf, fs::Metadata)> {
+    let dot_entries = super::dot_entries(config)
+        .into_iter()
+        .map(|(name, path, metadata)| (name.to_string(), path, metadata));
+    let entries = entries
+        .iter()
+        .filter_map(|entry| entry.as_ref().ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            match entry.metadata() {
+                Ok(metadata) => Some((name, entry.path(), metadata)),
+                Err(e) => {
+                    warn!("cannot read metadata of {}: {}", name, e);
+                    None
+                }
+            }
+        })
+        .filter(|(name, path, metadata)| {
+            filter::matches(name, metadata, config) && filter::matches_contents(path, metadata, config)
+        });
+    dot_entries.chain(entries).collect()
+}
+
+/// Displays path arguments as a JSON array, named as given on the command line.
+///
+/// The paths are not filtered, since they were named explicitly.
+///
+/// # Arguments
+///
+/// * `paths` - The paths to display with their own metadata (from
+///   `symlink_metadata`), already sorted
+/// * `config` - Configuration specifying display options
+/// * `out` - Destination for the JSON document
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn display_paths(paths: Vec<(PathBuf, fs::Metadata)>, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    write_entries(&path_entries(paths), config, out)
+}
+
+/// Names path arguments as given on the command line.
+pub(super) fn path_entries(paths: Vec<(PathBuf, fs::Metadata)>) -> Vec<(String, PathBuf, fs::Metadata)> {
+    paths
+        .into_iter()
+        .map(|(path, metadata)| (path.display().to_string(), path, metadata))
+        .collect()
+}
+
+/// Writes the listed entries as a pretty-printed JSON array.
+pub(super) fn write_entries(listed: &[(String, PathBuf, fs::Metadata)], config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let directory_sizes = compute_recursive_sizes(listed, config);
+    let hashes = compute_hashes(listed, config);
+    let paths: Vec<PathBuf> = listed.iter().map(|(_, path, _)| path.clone()).collect();
+    let mut exec_cells = exec_column::compute(&paths, &config.exec_columns, config.exec_timeout).into_iter();
+    let mut notes: HashMap<PathBuf, Notes> = HashMap::new();
+
+    let json_entries: Vec<JsonEntry> = listed
+        .iter()
+        .enumerate()
+        .map(|(index, (name, path, metadata))| {
+            let extended = extended_metadata(path).unwrap_or_default();
+            JsonEntry {
+                rank: config.rank.then_some(index + 1),
+                name: name.clone(),
+                path: path.display().to_string(),
+                file_type: get_file_type(metadata),
+                extension: Some(file_extension(path, metadata)).filter(|extension| !extension.is_empty()),
+                size: metadata.len(),
+                allocated: allocated_size(metadata),
+                recursive_size: directory_sizes.get(path).copied(),
+                mode: metadata.mode() & 0o7777,
+                octal: format!("{:o}", metadata.mode() & 0o7777),
+                uid: metadata.uid(),
+                user: user_name(metadata.uid()),
+                gid: metadata.gid(),
+                group: group_name(metadata.gid()),
+                inode: metadata.ino(),
+                links: metadata.nlink(),
+                modified: iso_time(metadata.modified().ok(), config.time_zone),
+                accessed: iso_time(metadata.accessed().ok(), config.time_zone),
+                changed: iso_time(changed_time(metadata), config.time_zone),
+                created: iso_time(extended.birth_time.or(metadata.created().ok()), config.time_zone),
+                mount_id: extended.mount_id,
+                target: metadata
+                    .file_type()
+                    .is_symlink()
+                    .then(|| fs::read_link(path).ok())
+                    .flatten()
+                    .map(|target| target.display().to_string()),
+                hash: hashes.get(path).cloned(),
+                comment: config.comments.then(|| comment(&mut notes, path)).flatten(),
+                exec: config
+                    .exec_columns
+                    .iter()
+                    .map(|column| column.name.clone())
+                    .zip(exec_cells.next().unwrap_or_default())
+                    .collect(),
+                // The `.` and `..` entries are named after other directories
+                problems: if matches!(name.as_str(), "." | "..") {
+                    Vec::new()
+                } else {
+                    failed_checks(path, config)
+                },
+            }
+        })
+        .collect();
+
+    serde_json::to_writer_pretty(&mut *out, &json_entries)?;
+    writeln!(out)
+}
+
+/// Returns an entry's comment, loading the notes file of its directory once.
+fn comment(notes: &mut HashMap<PathBuf, Notes>, path: &Path) -> Option<String> {
+    let directory = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let name = path.file_name()?.to_string_lossy();
+    notes
+        .entry(directory)
+        .or_insert_with_key(|directory| Notes::load(directory))
+        .comment(&name, path)
+}
+
+/// Formats an optional timestamp as ISO-8601, keeping None as JSON null.
+fn iso_time(time: Option<SystemTime>, time_zone: DisplayTimeZone) -> Option<String> {
+    time.map(|time| format_iso8601(time, time_zone))
+}
+
+/// Computes the recursive sizes of the listed directories for `--recursive-sizes`.
+fn compute_recursive_sizes(listed: &[(String, PathBuf, fs::Metadata)], config: &Config) -> HashMap<PathBuf, u64> {
+    if !config.recursive_sizes {
+        return HashMap::new();
+    }
+
+    let directories: Vec<PathBuf> = listed
+        .iter()
+        .filter(|(name, _, metadata)| metadata.is_dir() && name != "." && name != "..")
+        .map(|(_, path, _)| path.clone())
+        .collect();
+    let sizes = recursive_sizes(&directories);
+    directories.into_iter().zip(sizes).collect()
+}
+
+/// Computes the full content hashes of the listed entries for `--dir-hash`.
+fn compute_hashes(listed: &[(String, PathBuf, fs::Metadata)], config: &Config) -> HashMap<PathBuf, String> {
+    if !config.dir_hash {
+        return HashMap::new();
+    }
+
+    let paths: Vec<PathBuf> = listed
+        .iter()
+        .filter(|(name, path, _)| name != ".." && !is_virtual(path))
+        .map(|(_, path, _)| path.clone())
+        .collect();
+    let hashes = content_hashes(&paths);
+    paths
+        .into_iter()
+        .zip(hashes)
+        .filter_map(|(path, hash)| Some((path, hash?)))
+        .collect()
+}
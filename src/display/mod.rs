@@ -3,20 +3,67 @@
 //! This module provides the main entry point for displaying directory contents
 //! and delegates to specific formatters based on the configuration.
 
+pub mod classic;
+pub mod json;
+pub mod probe;
 pub mod simple;
+pub mod stat;
+pub mod svg;
 pub mod table;
+pub mod trash;
 pub mod tree;
+pub mod watch;
 
 use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use colored::*;
+use tracing::{debug, warn};
 
-use crate::config::Config;
+use crate::changes;
+use crate::config::{Config, ExportFormat};
+use crate::export;
+use crate::filter;
+use crate::sort;
+
+/// Lists the paths given on the command line, like `ls PATH...`.
+///
+/// File arguments (and, in long format, symlink arguments unless `-H` is
+/// given) are listed first as if they were entries of one directory, and
+/// unfiltered, since they were named explicitly. Each directory argument then
+/// follows as its own section, under a `path:` header when more than one path
+/// was given (unless `--no-headers`). A single directory argument is listed
+/// exactly as [`list_directory`] lists it.
+///
+/// # Arguments
+///
+/// * `paths` - The path arguments, in command-line order
+/// * `config` - Configuration specifying format and options
+///
+/// # Errors
+///
+/// Prints an error message to stderr for each path that does not exist, and
+/// if a directory cannot be read or the output cannot be written.
+pub fn list_paths(paths: &[PathBuf], config: &Config) {
+    if let [path] = paths {
+        if !is_file_argument(path, config) {
+            list_directory(&Config {
+                path: path.to_string_lossy().to_string(),
+                ..config.clone()
+            });
+            return;
+        }
+    }
+    write_output(config, |out| write_paths(paths, config, out));
+}
 
 /// Lists directory contents according to the provided configuration.
 ///
 /// This is the main entry point for directory listing. It reads the directory,
-/// sorts entries alphabetically, and delegates to the appropriate display module
-/// based on whether long format is requested.
+/// sorts entries as configured, and delegates to the appropriate display module
+/// based on whether long format is requested. With `-R`, every directory below
+/// the path is listed as its own section. With `--export`, the colored output
+/// is captured and converted before printing.
 ///
 /// # Arguments
 ///
@@ -24,28 +71,304 @@ use crate::config::Config;
 ///
 /// # Errors
 ///
-/// Prints an error message to stderr if the directory cannot be read.
+/// Prints an error message to stderr if the directory cannot be read or the
+/// output cannot be written.
 pub fn list_directory(config: &Config) {
+    if config.tree && config.watch {
+        debug!("using live tree display for {}", config.path);
+        watch::display(config);
+        return;
+    }
+
+    write_output(config, |out| {
+        if config.recursive {
+            write_recursive_listing(config, out)
+        } else {
+            write_listing(config, out)
+        }
+    });
+}
+
+/// Runs `write` on standard output, converting its output with `--export`,
+/// and reports write errors.
+fn write_output(config: &Config, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) {
+    let result = match config.export {
+        Some(ExportFormat::AnsiHtml) => {
+            let mut buffer = Vec::new();
+            write(&mut buffer).and_then(|()| {
+                let html = export::ansi_to_html(&String::from_utf8_lossy(&buffer));
+                io::stdout().lock().write_all(html.as_bytes())
+            })
+        }
+        Some(ExportFormat::Svg) | None => write(&mut io::stdout().lock()),
+    };
+
+    report_write_error(result);
+}
+
+/// Checks whether a path argument is listed as an entry rather than as a
+/// directory: files, broken symlinks, and in long format all symlinks unless
+/// `-H` is given (like `ls -l`). Missing paths are neither.
+fn is_file_argument(path: &Path, config: &Config) -> bool {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return false;
+    };
+    if metadata.file_type().is_symlink() && config.long_format && !config.tree && !config.dereference_args {
+        return true;
+    }
+    fs::metadata(path).map_or(true, |metadata| !metadata.is_dir())
+}
+
+/// Writes the file arguments, then each directory argument as a section.
+///
+/// With `-j`, everything goes into one JSON array instead, so the output
+/// stays a single document; each entry's `path` tells where it came from.
+fn write_paths(paths: &[PathBuf], config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let mut files = Vec::new();
+    let mut directories = Vec::new();
+    for path in paths {
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if is_file_argument(path, config) => files.push((path.clone(), metadata)),
+            Ok(_) => directories.push(path),
+            Err(e) => eprintln!("{}: {}: {}", "Error".red().bold(), path.display(), e),
+        }
+    }
+
+    if config.json {
+        return write_json_paths(files, &directories, config, out);
+    }
+
+    let mut first = true;
+    if !files.is_empty() {
+        write_file_arguments(files, config, out)?;
+        first = false;
+    }
+
+    for directory in directories {
+        let section = Config {
+            path: directory.to_string_lossy().to_string(),
+            ..config.clone()
+        };
+        if section.recursive {
+            // Recursive listings label every directory themselves
+            if !first {
+                writeln!(out)?;
+            }
+            write_recursive_listing(&section, out)?;
+        } else {
+            if !first {
+                writeln!(out)?;
+            }
+            if config.section_headers && paths.len() > 1 {
+                writeln!(out, "{}", format!("{}:", directory.display()).bold())?;
+            }
+            write_listing(&section, out)?;
+        }
+        first = false;
+    }
+    Ok(())
+}
+
+/// Writes the file arguments and the entries of every directory argument as
+/// one JSON array, files first.
+fn write_json_paths(
+    mut files: Vec<(PathBuf, fs::Metadata)>,
+    directories: &[&PathBuf],
+    config: &Config,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    debug!("using JSON display for {} paths", files.len() + directories.len());
+    files.sort_by_cached_key(|(path, _)| sort::path_key(path, config));
+    let mut listed = json::path_entries(files);
+    for directory in directories {
+        let section = Config {
+            path: directory.to_string_lossy().to_string(),
+            ..config.clone()
+        };
+        match fs::read_dir(directory) {
+            Ok(dir) => listed.extend(json::directory_entries(&sorted_entries(dir, &section), &section)),
+            Err(e) => eprintln!("{}: {}: {}", "Error".red().bold(), directory.display(), e),
+        }
+    }
+    json::write_entries(&listed, config, out)
+}
+
+/// Writes file arguments in the configured format, in sort order.
+fn write_file_arguments(mut files: Vec<(PathBuf, fs::Metadata)>, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    files.sort_by_cached_key(|(path, _)| sort::path_key(path, config));
+    if config.json {
+        debug!("using JSON display for {} file arguments", files.len());
+        json::display_paths(files, config, out)
+    } else if config.classic {
+        debug!("using classic display for {} file arguments", files.len());
+        classic::display_paths(files, config, out)
+    } else if config.long_format && !config.tree {
+        debug!("using table display for {} file arguments", files.len());
+        table::display_paths(files, config, out)
+    } else {
+        debug!("using simple display for {} file arguments", files.len());
+        simple::in_grid(config, out, |out| {
+            files
+                .iter()
+                .try_for_each(|(path, metadata)| simple::write_path(path, metadata, config, out))
+        })
+    }
+}
+
+/// Reports an error from writing the listing, unless the reader went away.
+fn report_write_error(result: io::Result<()>) {
+    // A closed pipe (e.g. `fls | head`) is not worth reporting
+    if let Err(e) = result {
+        if e.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("{}: {}", "Error".red().bold(), e);
+        }
+    }
+}
+
+/// Lists the directory and all directories below it for `-R`, like `ls -R`.
+///
+/// Each directory is listed as its own section in the configured format,
+/// preceded by a `path:` header unless `--no-headers` is given and separated
+/// from the previous section by a blank line. Subdirectories follow their
+/// parent in sort order (depth first), hidden ones only with `-a`, and
+/// symlinks to directories are not followed. Traversal stops at `-L/--depth`.
+fn write_recursive_listing(config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    // Directories still to list, with their depth below the listed directory;
+    // pushed in reverse so they are popped in sort order
+    let mut pending = vec![(PathBuf::from(&config.path), 0)];
+    let mut first = true;
+
+    while let Some((directory, depth)) = pending.pop() {
+        if !first {
+            writeln!(out)?;
+        }
+        first = false;
+        if config.section_headers {
+            writeln!(out, "{}", format!("{}:", directory.display()).bold())?;
+        }
+
+        let section = Config {
+            path: directory.to_string_lossy().to_string(),
+            ..config.clone()
+        };
+        write_listing(&section, out)?;
+
+        if depth < config.max_depth() {
+            let subdirectories = subdirectories(&directory, config);
+            pending.extend(subdirectories.into_iter().rev().map(|path| (path, depth + 1)));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the visible subdirectories of a directory in sort order, without
+/// following symlinks.
+fn subdirectories(directory: &Path, config: &Config) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+    let mut subdirectories: Vec<fs::DirEntry> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .filter(|entry| filter::is_visible(&entry.file_name().to_string_lossy(), config))
+        .filter(|entry| !filter::is_git_ignored(&entry.path(), true, config))
+        .collect();
+    sort::sort_entries(&mut subdirectories, config);
+    subdirectories.into_iter().map(|entry| entry.path()).collect()
+}
+
+/// Reads the directory and writes the listing in the configured format.
+fn write_listing(config: &Config, out: &mut dyn Write) -> io::Result<()> {
     let dir = match fs::read_dir(&config.path) {
         Ok(dir) => dir,
         Err(e) => {
             eprintln!("{}: {}", "Error".red().bold(), e);
-            return;
+            return Ok(());
         }
     };
 
-    let mut entries: Vec<_> = dir.collect();
-    entries.sort_by(|a, b| {
-        let a_name = a.as_ref().unwrap().file_name();
-        let b_name = b.as_ref().unwrap().file_name();
-        a_name.cmp(&b_name)
-    });
+    if config.low_memory {
+        return write_streamed_listing(dir, config, out);
+    }
+
+    let entries = sorted_entries(dir, config);
+    let changes = config.changed.then(|| changes::detect(Path::new(&config.path), &entries));
 
-    if config.tree {
-        tree::display(&entries, config);
+    if config.tree && config.export == Some(ExportFormat::Svg) {
+        debug!("using SVG tree export for {}", config.path);
+        svg::display(config, out)
+    } else if config.tree {
+        debug!("using tree display for {}", config.path);
+        tree::display(&entries, config, out)
+    } else if config.json {
+        debug!("using JSON display for {}", config.path);
+        json::display(&entries, config, out)
+    } else if config.classic {
+        debug!("using classic display for {}", config.path);
+        classic::display(&entries, config, out)
     } else if config.long_format {
-        table::display(&entries, config);
+        debug!("using table display for {}", config.path);
+        table::display(&entries, config, changes.as_ref(), out)
     } else {
-        simple::display(&entries, config);
+        debug!("using simple display for {}", config.path);
+        simple::display(&entries, config, changes.as_ref(), out)
+    }
+}
+
+/// Reads a directory's entries, drops gitignored ones with `--git-ignore`, and
+/// sorts them as configured.
+fn sorted_entries(dir: fs::ReadDir, config: &Config) -> Vec<io::Result<fs::DirEntry>> {
+    let mut entries: Vec<_> = dir.collect();
+    if config.git_ignore {
+        entries.retain(|entry| {
+            !entry.as_ref().is_ok_and(|entry| {
+                let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+                filter::is_git_ignored(&entry.path(), is_dir, config)
+            })
+        });
+    }
+    sort::sort_entry_results(&mut entries, config);
+    entries
+}
+
+/// Writes the listing entry by entry as the directory is read, for `--low-memory`.
+///
+/// Entries are neither collected nor sorted, so memory use stays the same
+/// however large the directory is, and output starts before the directory
+/// has been read to the end.
+fn write_streamed_listing(dir: fs::ReadDir, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    if config.long_format {
+        debug!("using streamed table display for {}", config.path);
+        return table::display_streamed(dir, config, out);
+    }
+
+    debug!("using streamed simple display for {}", config.path);
+    simple::display(&[], config, None, out)?;
+    for entry in dir {
+        match entry {
+            Ok(entry) => simple::write_entry(&entry, config, None, out)?,
+            Err(e) => warn!("cannot read directory entry: {}", e),
+        }
     }
-}
\ No newline at end of file
+    Ok(())
+}
+
+/// Returns the `.` and `..` pseudo-entries of the listed directory for `--all-all`.
+///
+/// They pass the metadata and content filters like any other entry, but are
+/// never hidden as dotfiles. Entries whose metadata cannot be read are skipped.
+pub(super) fn dot_entries(config: &Config) -> Vec<(&'static str, PathBuf, fs::Metadata)> {
+    if !config.dot_entries {
+        return Vec::new();
+    }
+
+    let directory = Path::new(&config.path);
+    [(".", directory.to_path_buf()), ("..", directory.join(".."))]
+        .into_iter()
+        .filter_map(|(name, path)| {
+            let metadata = fs::metadata(&path).ok()?;
+            (filter::matches_metadata(name, &metadata, config) && filter::matches_contents(&path, &metadata, config))
+                .then_some((name, path, metadata))
+        })
+        .collect()
+}
@@ -0,0 +1,190 @@
+//! Configuration probe implementation.
+//!
+//! This module provides `--probe`: a field/value table of the settings a
+//! listing would use — output format, columns, sorting, filters, colors, and
+//! the configuration file — resolved from the command line and environment
+//! without reading any of the listed directories.
+
+use clap::ValueEnum;
+use std::path::PathBuf;
+use tabled::{builder::Builder, settings::Style};
+
+use crate::config::{config_file, Config, DelimitedFormat};
+use crate::formatting::{format_size, DisplayTimeZone};
+
+use super::table;
+
+/// Displays the resolved configuration of a listing as a field/value table.
+///
+/// # Arguments
+///
+/// * `paths` - The path arguments, which are shown but not read
+/// * `config` - Configuration resolved from the command line and environment
+pub fn display(paths: &[PathBuf], config: &Config) {
+    let mut builder = Builder::default();
+    builder.push_record(["Field", "Value"]);
+
+    let mut field = |label: &str, value: String| builder.push_record([label.to_string(), value]);
+
+    field(
+        "Paths",
+        paths.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join("\n"),
+    );
+    field("Format", format_name(config).to_string());
+    field("Columns", columns(config).join(", "));
+    let mut sort = value_name(config.sort);
+    if config.reverse {
+        sort.push_str(", reversed");
+    }
+    if let Some(column) = &config.sort_column {
+        sort = format!("by column {}", column);
+    }
+    field("Sort", sort);
+    field("Tiebreak", value_name(config.tiebreak));
+    field("Filters", filters(config).join("\n"));
+    if config.tree || config.recursive {
+        field("Depth", config.max_depth().to_string());
+    }
+    field(
+        "Colors",
+        if colored::control::SHOULD_COLORIZE.should_colorize() { "On" } else { "Off" }.to_string(),
+    );
+    field(
+        "Time Zone",
+        match config.time_zone {
+            DisplayTimeZone::Local => "Local".to_string(),
+            DisplayTimeZone::Utc => "UTC".to_string(),
+            DisplayTimeZone::Named(tz) => tz.to_string(),
+        },
+    );
+    field(
+        "Locale",
+        format!("{:?} (times), {:?} (numbers)", config.locale.time, config.locale.numeric),
+    );
+    field(
+        "Config File",
+        match config_file() {
+            _ if config.no_config => "Not read (--no-config)".to_string(),
+            Some(path) if path.is_file() => path.display().to_string(),
+            Some(path) => format!("{} (not found)", path.display()),
+            None => "None (neither XDG_CONFIG_HOME nor HOME is set)".to_string(),
+        },
+    );
+    if !config.config_defaults.is_empty() {
+        field("Defaults", config.config_defaults.join("\n"));
+    }
+    let thresholds = config.size_thresholds;
+    field(
+        "Size Colors",
+        format!(
+            "yellow from {}, magenta from {}, red from {}",
+            format_size(thresholds.medium),
+            format_size(thresholds.large),
+            format_size(thresholds.huge)
+        ),
+    );
+    let extensions = config.openers.extensions();
+    field(
+        "Openers",
+        if extensions.is_empty() { "None".to_string() } else { extensions.join(", ") },
+    );
+
+    println!("{}", builder.build().with(Style::modern()));
+}
+
+/// Names the output format the listing would use.
+fn format_name(config: &Config) -> &'static str {
+    if config.json {
+        "JSON"
+    } else if config.tree && config.watch {
+        "Live tree"
+    } else if config.tree {
+        "Tree"
+    } else if config.classic {
+        "Classic (ls -l)"
+    } else if config.format == Some(DelimitedFormat::Csv) {
+        "CSV"
+    } else if config.format == Some(DelimitedFormat::Tsv) {
+        "TSV"
+    } else if config.long_format && (config.deterministic || config.low_memory) {
+        "Tab-separated table"
+    } else if config.long_format && config.compact {
+        "Compact table"
+    } else if config.long_format {
+        "Table"
+    } else if config.grid_width.is_some() {
+        "Grid"
+    } else {
+        "Simple"
+    }
+}
+
+/// Returns the columns the listing would show, in order.
+fn columns(config: &Config) -> Vec<String> {
+    if config.classic {
+        ["Permissions", "Links", "User", "Group", "Size", "Time", "Name"].map(String::from).to_vec()
+    } else if config.long_format && !config.tree && !config.json {
+        table::columns(config)
+    } else {
+        vec!["Name".to_string()]
+    }
+}
+
+/// Describes the active filters, one per line, or "None".
+fn filters(config: &Config) -> Vec<String> {
+    let mut filters = Vec::new();
+    if config.dot_entries {
+        filters.push("Hidden files and . and .. shown".to_string());
+    } else if config.show_hidden {
+        filters.push("Hidden files shown".to_string());
+    }
+    if config.git_ignore {
+        filters.push("Gitignored entries hidden".to_string());
+    }
+    if !config.name_globs.is_empty() {
+        filters.push(format!("Names match {}", pattern_list(&config.name_globs, " or ")));
+    }
+    if let Some(regex) = &config.name_regex {
+        filters.push(format!("Names match /{}/", regex.as_str()));
+    }
+    if let Some(age) = config.newer_than {
+        filters.push(format!("Modified within {}", humantime::format_duration(age)));
+    }
+    if let Some(age) = config.older_than {
+        filters.push(format!("Modified more than {} ago", humantime::format_duration(age)));
+    }
+    if config.executables_only {
+        filters.push("Executables only".to_string());
+    }
+    if let Some(pattern) = &config.contains {
+        filters.push(format!("Contents match /{}/", pattern.as_str()));
+    }
+    if let Some(user) = &config.owner_user {
+        filters.push(format!("Owned by user {}", user));
+    }
+    if let Some(group) = &config.owner_group {
+        filters.push(format!("Owned by group {}", group));
+    }
+    if !config.exclude_patterns.is_empty() {
+        filters.push(format!("Names excluding {}", pattern_list(&config.exclude_patterns, " and ")));
+    }
+    if config.ignore_backups {
+        filters.push(format!("Backups hidden ({})", pattern_list(&config.backup_patterns, " ")));
+    }
+    if filters.is_empty() {
+        filters.push("None".to_string());
+    }
+    filters
+}
+
+/// Joins glob patterns as they were written, with `separator` between them.
+fn pattern_list(patterns: &[glob::Pattern], separator: &str) -> String {
+    patterns.iter().map(glob::Pattern::as_str).collect::<Vec<_>>().join(separator)
+}
+
+/// Returns the command-line spelling of an option value.
+fn value_name(value: impl ValueEnum) -> String {
+    value
+        .to_possible_value()
+        .map_or_else(String::new, |value| value.get_name().to_string())
+}
@@ -1,59 +1,292 @@
 //! Simple format display implementation.
 //!
 //! This module provides the simple (non-table) display format that shows
-//! file names in a vertical list, similar to basic `ls` output but with
-//! colors and optional interactive features.
+//! file names, similar to basic `ls` output but with colors and optional
+//! interactive features. In a terminal, names are packed into columns that
+//! fill its width, like `ls`; piped output lists one name per line.
 
+use colored::*;
 use std::fs;
+use std::io::{self, Write};
 use std::path::Path;
+use tracing::warn;
 
-use crate::colors::{get_colored_name, make_clickable_link};
+use crate::changes::ChangeSet;
+use crate::checks::check_badges;
+use crate::colors::{get_colored_name, link_suffix, make_clickable_link};
 use crate::config::Config;
+use crate::file_info::{get_file_type, is_recently_modified, link_target, NEW_BADGE};
+use crate::filter;
+use crate::icons::icon_prefix;
+use crate::preview::preview_lines;
+use crate::terminal::visible_width;
 
-/// Displays directory entries in simple format (one file per line).
+/// Spaces between the columns of the grid layout
+const COLUMN_GAP: usize = 2;
+
+/// Displays directory entries in simple format.
 ///
-/// This function outputs file names in a simple vertical list with color coding
-/// based on file type. If interactive mode is enabled, file names become
-/// clickable hyperlinks.
+/// This function outputs file names with color coding based on file type, in
+/// columns when a grid width is configured and one per line otherwise. If
+/// interactive mode is enabled, file names become clickable hyperlinks.
 ///
 /// # Arguments
 ///
 /// * `entries` - Iterator over directory entries
 /// * `config` - Configuration specifying display options
+/// * `changes` - Changes since the previous run, for `--changed`
+/// * `out` - Destination for the listing
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
 ///
 /// # Features
 ///
 /// - Color-coded file names based on type
+/// - Grid layout fitting the terminal width
 /// - Optional clickable hyperlinks in interactive mode
-/// - Hidden file filtering based on configuration
+/// - Hidden file and age filtering based on configuration
+/// - Optional `.` and `..` pseudo-entries (`--all-all`)
+/// - Optional preview of small text files below their names
+/// - Optional change markers and removed entries relative to the previous run
 /// - Graceful error handling for unreadable files
-pub fn display(entries: &[Result<fs::DirEntry, std::io::Error>], config: &Config) {
-    for entry in entries {
-        let Ok(entry) = entry else { continue };
+pub fn display(
+    entries: &[Result<fs::DirEntry, std::io::Error>],
+    config: &Config,
+    changes: Option<&ChangeSet>,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    in_grid(config, out, |out| write_lines(entries, config, changes, out))
+}
+
+/// Writes the `.` and `..` entries, the directory entries, and removed entries one per line.
+fn write_lines(
+    entries: &[Result<fs::DirEntry, std::io::Error>],
+    config: &Config,
+    changes: Option<&ChangeSet>,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    for (name, path, metadata) in super::dot_entries(config) {
+        let colored_name = get_colored_name(name, &path, &metadata);
+        if changes.is_some() {
+            write!(out, "  ")?;
+        }
+        write!(out, "{}", icon_prefix(name, &get_file_type(&metadata), false, config))?;
+        if config.interactive {
+            writeln!(out, "{}", make_clickable_link(name, &path, &colored_name))?;
+        } else {
+            writeln!(out, "{}", colored_name)?;
+        }
+    }
 
-        let file_name = entry.file_name();
-        let file_name_str = file_name.to_string_lossy();
+    for entry in entries {
+        match entry {
+            Ok(entry) => write_entry(entry, config, changes, out)?,
+            Err(e) => warn!("cannot read directory entry: {}", e),
+        }
+    }
 
-        if !config.show_hidden && file_name_str.starts_with('.') {
-            continue;
+    if let Some(changes) = changes {
+        for name in changes.removed.iter().filter(|name| filter::is_visible(name, config)) {
+            writeln!(out, "{} {}", "-".red().bold(), name.red())?;
         }
+    }
+    Ok(())
+}
+
+/// Runs `write` and, with a grid width configured, lays out the lines it
+/// wrote in columns.
+///
+/// # Arguments
+///
+/// * `config` - Configuration holding the grid width, if any
+/// * `out` - Destination for the listing
+/// * `write` - Writes the listing one name per line
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn in_grid(
+    config: &Config,
+    out: &mut dyn Write,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> io::Result<()> {
+    let Some(width) = config.grid_width else {
+        return write(out);
+    };
+    let mut buffer = Vec::new();
+    write(&mut buffer)?;
+    let text = String::from_utf8_lossy(&buffer);
+    let cells: Vec<&str> = text.lines().collect();
+    write_grid(&cells, width, out)
+}
 
-        let metadata = match entry.metadata() {
-            Ok(metadata) => metadata,
-            Err(_) => {
-                println!("{}", file_name_str);
-                continue;
+/// Writes cells in columns, filled top to bottom and then left to right like `ls`.
+fn write_grid(cells: &[&str], width: usize, out: &mut dyn Write) -> io::Result<()> {
+    let widths: Vec<usize> = cells.iter().map(|cell| visible_width(cell)).collect();
+    let (rows, column_widths) = grid_shape(&widths, width);
+
+    for row in 0..rows {
+        let mut line = String::new();
+        for (column, column_width) in column_widths.iter().enumerate() {
+            let index = column * rows + row;
+            let Some(cell) = cells.get(index) else {
+                break;
+            };
+            line.push_str(cell);
+            // Pad unless nothing follows in this row
+            if index + rows < cells.len() {
+                line.push_str(&" ".repeat(column_width - widths[index] + COLUMN_GAP));
             }
-        };
+        }
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
 
-        let colored_name = get_colored_name(&file_name_str, &metadata);
-        
-        if config.interactive {
-            let full_path = Path::new(&config.path).join(&file_name);
-            let clickable_name = make_clickable_link(&file_name_str, &full_path, &colored_name);
-            println!("{}", clickable_name);
-        } else {
-            println!("{}", colored_name);
+/// Finds the fewest rows whose layout fits the width, and the widths of its columns.
+fn grid_shape(widths: &[usize], width: usize) -> (usize, Vec<usize>) {
+    // No layout fits in fewer rows than this, since every row holds at most
+    // `width` columns of cells plus the gap after the last one
+    let total: usize = widths.iter().map(|cell| cell + COLUMN_GAP).sum();
+    let fewest_rows = total.div_ceil(width + COLUMN_GAP).max(1);
+
+    for rows in fewest_rows..widths.len() {
+        let column_widths: Vec<usize> = widths
+            .chunks(rows)
+            .map(|column| column.iter().copied().max().unwrap_or(0))
+            .collect();
+        let line_width = column_widths.iter().sum::<usize>() + COLUMN_GAP * (column_widths.len() - 1);
+        if line_width <= width {
+            return (rows, column_widths);
         }
     }
+    (widths.len(), vec![widths.iter().copied().max().unwrap_or(0)])
+}
+
+/// Writes one directory entry in simple format, unless the filters hide it.
+///
+/// # Arguments
+///
+/// * `entry` - The directory entry
+/// * `config` - Configuration specifying display options
+/// * `changes` - Changes since the previous run, for `--changed`
+/// * `out` - Destination for the listing
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn write_entry(
+    entry: &fs::DirEntry,
+    config: &Config,
+    changes: Option<&ChangeSet>,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let file_name = entry.file_name();
+    let file_name_str = file_name.to_string_lossy();
+
+    if !filter::is_visible(&file_name_str, config) {
+        return Ok(());
+    }
+
+    let metadata = match entry.metadata() {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            warn!("cannot read metadata of {}: {}", file_name_str, e);
+            writeln!(out, "{}", file_name_str)?;
+            return Ok(());
+        }
+    };
+
+    if !filter::matches_metadata(&file_name_str, &metadata, config)
+        || !filter::matches_contents(&entry.path(), &metadata, config)
+    {
+        return Ok(());
+    }
+
+    let colored_name = get_colored_name(&file_name_str, &entry.path(), &metadata);
+    let link = link_target(&entry.path(), &metadata);
+    if let Some(changes) = changes {
+        write!(out, "{} ", change_marker(changes, &file_name_str))?;
+    }
+    let broken = link.as_ref().is_some_and(|(_, broken)| *broken);
+    write!(out, "{}", icon_prefix(&file_name_str, &get_file_type(&metadata), broken, config))?;
+
+    if config.interactive {
+        let full_path = Path::new(&config.path).join(&file_name);
+        let clickable_name = make_clickable_link(&file_name_str, &full_path, &colored_name);
+        write!(out, "{}", clickable_name)?;
+    } else {
+        write!(out, "{}", colored_name)?;
+    }
+    if let Some((target, broken)) = link {
+        write!(out, "{}", link_suffix(&target, broken))?;
+    }
+    if config.highlight_new.is_some_and(|within| is_recently_modified(&metadata, within)) {
+        write!(out, " {}", NEW_BADGE.green().bold())?;
+    }
+    let badges = check_badges(&entry.path(), config);
+    if !badges.is_empty() {
+        write!(out, "{}", badges.red().bold())?;
+    }
+    match changes.and_then(|changes| changes.drift.get(file_name_str.as_ref())) {
+        Some(drift) => writeln!(out, "  {}", format!("({})", drift.describe()).magenta())?,
+        None => writeln!(out)?,
+    }
+
+    if let Some(lines) = config.preview {
+        let full_path = Path::new(&config.path).join(&file_name);
+        for line in preview_lines(&full_path, &metadata, lines, true).unwrap_or_default() {
+            writeln!(out, "    {}", line.bright_black())?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes one path argument in simple format, named as given on the command line.
+///
+/// # Arguments
+///
+/// * `path` - The path argument
+/// * `metadata` - The path's own metadata (from `symlink_metadata`)
+/// * `config` - Configuration specifying display options
+/// * `out` - Destination for the listing
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn write_path(path: &Path, metadata: &fs::Metadata, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let name = path.display().to_string();
+    let colored_name = get_colored_name(&name, path, metadata);
+    let link = link_target(path, metadata);
+    let broken = link.as_ref().is_some_and(|(_, broken)| *broken);
+    write!(out, "{}", icon_prefix(&name, &get_file_type(metadata), broken, config))?;
+    if config.interactive {
+        write!(out, "{}", make_clickable_link(&name, path, &colored_name))?;
+    } else {
+        write!(out, "{}", colored_name)?;
+    }
+    if let Some((target, broken)) = link {
+        write!(out, "{}", link_suffix(&target, broken))?;
+    }
+    if config.highlight_new.is_some_and(|within| is_recently_modified(metadata, within)) {
+        write!(out, " {}", NEW_BADGE.green().bold())?;
+    }
+    let badges = check_badges(path, config);
+    if !badges.is_empty() {
+        write!(out, "{}", badges.red().bold())?;
+    }
+    writeln!(out)
+}
+
+/// Returns the marker shown before an entry for `--changed`: `+` new, `~` modified,
+/// `!` ownership or permissions changed.
+fn change_marker(changes: &ChangeSet, name: &str) -> ColoredString {
+    match changes.label(name) {
+        Some("new") => "+".green().bold(),
+        Some(_) => "~".yellow().bold(),
+        None if changes.drift.contains_key(name) => "!".magenta().bold(),
+        None => " ".normal(),
+    }
 }
\ No newline at end of file
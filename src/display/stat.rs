@@ -0,0 +1,124 @@
+//! Single-file stat display implementation.
+//!
+//! This module provides the `fls stat FILE` view: a transposed, two-column
+//! (field/value) table with the technical details of one file, similar to
+//! `stat(1)` but with human-readable labels.
+
+use colored::*;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::time::SystemTime;
+use tabled::{builder::Builder, settings::Style};
+
+use crate::config::Config;
+use crate::file_info::{changed_time, FileInfo};
+use crate::statx::extended_metadata;
+use crate::formatting::{
+    format_octal_permissions, format_size, format_symbolic_permissions, format_timestamp, DisplayTimeZone,
+};
+
+/// Displays detailed information about a single file as a field/value table.
+///
+/// Symlinks are not followed, so the link itself is described and its target
+/// is shown as a separate field.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to describe
+/// * `config` - Configuration specifying display options
+///
+/// # Errors
+///
+/// Prints an error message to stderr if the file cannot be accessed.
+pub fn display(path: &Path, config: &Config) {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            eprintln!("{}: {}: {}", "Error".red().bold(), path.display(), e);
+            return;
+        }
+    };
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    let file_info = FileInfo::from_metadata_with_path(name.clone(), &metadata, path, config);
+
+    let mut builder = Builder::default();
+    builder.push_record(["Field", "Value"]);
+
+    let mut field = |label: &str, value: String| builder.push_record([label.to_string(), value]);
+
+    field("Name", name);
+    field("Path", absolute_path(path));
+    field("Type", file_info.file_type.clone());
+    if metadata.file_type().is_symlink() {
+        let target = fs::read_link(path)
+            .map(|target| target.display().to_string())
+            .unwrap_or_else(|_| "?".to_string());
+        field("Symlink Target", target);
+    }
+    field("Size", format!("{} ({} bytes)", format_size(metadata.len()), metadata.len()));
+    field("Blocks", format!("{} (IO block: {})", metadata.blocks(), metadata.blksize()));
+    let extended = extended_metadata(path).unwrap_or_default();
+    field("Device", metadata.dev().to_string());
+    field(
+        "Mount ID",
+        extended.mount_id.map_or_else(|| "Unsupported".to_string(), |id| id.to_string()),
+    );
+    field("Inode", metadata.ino().to_string());
+    field("Links", metadata.nlink().to_string());
+    field(
+        "Permissions",
+        format!("{} ({})", format_symbolic_permissions(&metadata), format_octal_permissions(&metadata)),
+    );
+    field("User Permission", file_info.user_perms.clone());
+    field("Group Permission", file_info.group_perms.clone());
+    field("Other Permission", file_info.other_perms.clone());
+    field("User", format!("{} ({})", file_info.user, metadata.uid()));
+    field("Group", format!("{} ({})", file_info.group, metadata.gid()));
+    let time_zone = config.time_zone;
+    field("Accessed", format_optional_time(metadata.accessed().ok(), time_zone));
+    field("Modified", format_optional_time(metadata.modified().ok(), time_zone));
+    field("Changed", format_optional_time(changed_time(&metadata), time_zone));
+    field(
+        "Created",
+        format_optional_time(extended.birth_time.or(metadata.created().ok()), time_zone),
+    );
+    field("Extended Attributes", extended_attributes(path));
+
+    println!("{}", builder.build().with(Style::modern()));
+}
+
+/// Resolves a path to an absolute path string without following symlinks.
+fn absolute_path(path: &Path) -> String {
+    if path.is_absolute() {
+        path.display().to_string()
+    } else {
+        std::env::current_dir()
+            .unwrap_or_default()
+            .join(path)
+            .display()
+            .to_string()
+    }
+}
+
+fn format_optional_time(time: Option<SystemTime>, time_zone: DisplayTimeZone) -> String {
+    time.map(|time| format_timestamp(time, time_zone))
+        .unwrap_or_else(|| "Unsupported".to_string())
+}
+
+/// Lists extended attribute names, or "None" if the file has none or they cannot be read.
+fn extended_attributes(path: &Path) -> String {
+    let names: Vec<String> = xattr::list(path)
+        .map(|attrs| attrs.map(|name| name.to_string_lossy().to_string()).collect())
+        .unwrap_or_default();
+
+    if names.is_empty() {
+        "None".to_string()
+    } else {
+        names.join("\n")
+    }
+}
@@ -0,0 +1,216 @@
+//! SVG tree diagram export.
+//!
+//! This module implements `--tree --export svg`: the same hierarchy as the tree
+//! view, drawn as labelled boxes joined by connectors, for use in documentation
+//! and presentations. With `--scale-by-size`, each box gets a bar proportional
+//! to its (recursive) size.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use unicode_width::UnicodeWidthStr;
+
+use super::tree::read_and_sort_entries;
+use crate::config::Config;
+use crate::dir_size::recursive_size;
+use crate::export::escape_html;
+use crate::file_info::{format_entry_size, get_file_type};
+
+/// Vertical distance between consecutive rows
+const ROW_HEIGHT: f64 = 28.0;
+const BOX_HEIGHT: f64 = 20.0;
+/// Horizontal offset of each tree level
+const INDENT: f64 = 24.0;
+/// Approximate advance of one character of the 12px monospace label font (wide characters take two)
+const CHAR_WIDTH: f64 = 7.5;
+const PADDING: f64 = 8.0;
+const MARGIN: f64 = 10.0;
+/// Length of the size bar of the largest entry
+const BAR_MAX_WIDTH: f64 = 200.0;
+
+/// One box of the diagram, in display order.
+struct Row {
+    name: String,
+    file_type: String,
+    depth: usize,
+    size: u64,
+    /// Index of the parent row (`None` for the root)
+    parent: Option<usize>,
+}
+
+/// Writes the directory tree as an SVG document.
+///
+/// # Arguments
+///
+/// * `config` - Configuration specifying the root path, depth, and filters
+/// * `out` - Destination for the SVG document
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn display(config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let root = Path::new(&config.path);
+    let mut rows = vec![Row {
+        name: config.path.clone(),
+        file_type: "Directory".to_string(),
+        depth: 0,
+        size: entry_size(root, config),
+        parent: None,
+    }];
+    collect_rows(root, 0, 0, config, &mut rows);
+
+    write_svg(&rows, config, out)
+}
+
+/// Appends the visible entries below `path` to `rows`, depth first.
+fn collect_rows(path: &Path, parent: usize, depth: usize, config: &Config, rows: &mut Vec<Row>) {
+    if depth >= config.max_depth() {
+        return;
+    }
+
+    for entry in read_and_sort_entries(path, config) {
+        let entry_path = entry.path();
+        let file_type = fs::symlink_metadata(&entry_path)
+            .map(|metadata| get_file_type(&metadata))
+            .unwrap_or_else(|_| "Unknown".to_string());
+        let index = rows.len();
+        rows.push(Row {
+            name: entry.file_name().to_string_lossy().to_string(),
+            file_type,
+            depth: depth + 1,
+            size: entry_size(&entry_path, config),
+            parent: Some(parent),
+        });
+
+        // Symlinked directories are not followed, as in the tree view
+        if rows[index].file_type == "Directory" {
+            collect_rows(&entry_path, index, depth + 1, config, rows);
+        }
+    }
+}
+
+/// Returns the size shown by the size bar, or 0 when sizes are not requested.
+fn entry_size(path: &Path, config: &Config) -> u64 {
+    if !config.scale_by_size {
+        return 0;
+    }
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => recursive_size(path),
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    }
+}
+
+/// Fill and stroke colors of a box, matching the terminal color scheme.
+fn box_colors(file_type: &str) -> (&'static str, &'static str) {
+    match file_type {
+        "Directory" => ("#dbeafe", "#2563eb"),
+        "Executable" => ("#dcfce7", "#16a34a"),
+        "Symlink" => ("#cffafe", "#0891b2"),
+        "FIFO" | "Block Device" | "Character Device" => ("#fef9c3", "#ca8a04"),
+        "Socket" => ("#fae8ff", "#c026d3"),
+        _ => ("#f3f4f6", "#6b7280"),
+    }
+}
+
+fn row_x(row: &Row) -> f64 {
+    MARGIN + row.depth as f64 * INDENT
+}
+
+fn row_y(index: usize) -> f64 {
+    MARGIN + index as f64 * ROW_HEIGHT
+}
+
+fn box_width(row: &Row) -> f64 {
+    row.name.width() as f64 * CHAR_WIDTH + 2.0 * PADDING
+}
+
+/// Lays out the rows and writes the SVG document.
+fn write_svg(rows: &[Row], config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let largest = rows.iter().map(|row| row.size).max().unwrap_or(0).max(1);
+    let bar_width = |row: &Row| BAR_MAX_WIDTH * row.size as f64 / largest as f64;
+
+    let width = rows
+        .iter()
+        .map(|row| {
+            let mut right = row_x(row) + box_width(row);
+            if config.scale_by_size {
+                // Bar plus its size label
+                right += 6.0 + bar_width(row) + 6.0 + 8.0 * CHAR_WIDTH;
+            }
+            right
+        })
+        .fold(0.0, f64::max)
+        + MARGIN;
+    let height = rows.len() as f64 * ROW_HEIGHT + 2.0 * MARGIN - (ROW_HEIGHT - BOX_HEIGHT);
+
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {:.0} {:.0}" font-family="monospace" font-size="12">"#,
+        width, height, width, height
+    )?;
+    writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+
+    // Connectors first, so boxes are drawn on top of them
+    for (index, row) in rows.iter().enumerate() {
+        if let Some(parent) = row.parent {
+            let start_x = row_x(&rows[parent]) + INDENT / 2.0;
+            let start_y = row_y(parent) + BOX_HEIGHT;
+            let end_y = row_y(index) + BOX_HEIGHT / 2.0;
+            writeln!(
+                out,
+                r##"<path d="M{:.1} {:.1} V{:.1} H{:.1}" fill="none" stroke="#9ca3af"/>"##,
+                start_x,
+                start_y,
+                end_y,
+                row_x(row)
+            )?;
+        }
+    }
+
+    for (index, row) in rows.iter().enumerate() {
+        let (x, y) = (row_x(row), row_y(index));
+        let (fill, stroke) = box_colors(&row.file_type);
+        let weight = if row.file_type == "Directory" { "bold" } else { "normal" };
+        writeln!(
+            out,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="3" fill="{}" stroke="{}"/>"#,
+            x,
+            y,
+            box_width(row),
+            BOX_HEIGHT,
+            fill,
+            stroke
+        )?;
+        writeln!(
+            out,
+            r##"<text x="{:.1}" y="{:.1}" fill="#111827" font-weight="{}">{}</text>"##,
+            x + PADDING,
+            y + 14.0,
+            weight,
+            escape_html(&row.name)
+        )?;
+
+        if config.scale_by_size {
+            let bar_x = x + box_width(row) + 6.0;
+            writeln!(
+                out,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"/>"#,
+                bar_x,
+                y + 6.0,
+                bar_width(row),
+                BOX_HEIGHT - 12.0,
+                stroke
+            )?;
+            writeln!(
+                out,
+                r##"<text x="{:.1}" y="{:.1}" fill="#6b7280">{}</text>"##,
+                bar_x + bar_width(row) + 6.0,
+                y + 14.0,
+                format_entry_size(row.size, config)
+            )?;
+        }
+    }
+
+    writeln!(out, "</svg>")
+}
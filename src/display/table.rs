@@ -2,16 +2,76 @@
 //!
 //! This module provides the detailed table display format that shows comprehensive
 //! file information including permissions, ownership, size, and modification time.
-//! It handles color application after table generation to maintain proper alignment.
+//! Cells are colored before the table is built; the table measures them without
+//! their escape sequences, so colors and hyperlinks never affect alignment.
 
+use colored::*;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
-use tabled::{settings::Style, Table};
+use std::io::{self, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::warn;
+use tabled::{
+    settings::{
+        format::Format,
+        location::ByColumnName,
+        object::{Cell, Columns},
+        peaker::Priority,
+        Alignment, Concat, Remove, Style, Width,
+    },
+    builder::Builder,
+    Table, Tabled,
+};
 
-use crate::colors::{get_colored_name, get_colored_size, make_clickable_link};
-use crate::config::Config;
-use crate::file_info::FileInfo;
-use crate::formatting::format_size;
+use crate::changes::ChangeSet;
+use crate::checks::{check_badges, orphaned_owners};
+use crate::colors::{get_colored_name, get_colored_size, link_suffix, make_clickable_link, make_explanation_link};
+use crate::comments::Notes;
+use crate::config::{Aggregate, ColumnAlignment, Config, DelimitedFormat, GroupBy, HeatBy, LinkSizes, TimeColumns};
+use crate::dir_hash::{content_hash, content_hashes, SHORT_HASH_LEN};
+use crate::exec_column;
+use crate::dir_size::{recursive_inode_counts, recursive_inodes, recursive_size, recursive_sizes};
+use crate::file_info::{allocated_size, changed_time, file_extension, format_entry_size, format_listing_time, get_file_type, is_executable, link_target, FileInfo, LINK_ARROW};
+use crate::filter;
+use crate::formatting::explain_permissions;
+use crate::icons::icon_prefix;
+use crate::preview::preview_lines;
+use crate::terminal::strip_escapes;
+use crate::virtual_fs::{entry_filesystem, is_virtual};
+
+/// An entry selected for display together with its metadata.
+struct ListedEntry {
+    /// Name shown in the Name column
+    name: String,
+    /// Full path used for item counts, directory sizes, and hyperlinks
+    path: PathBuf,
+    metadata: fs::Metadata,
+    /// Metadata of a symlink's target for `--link-sizes target` (`None` otherwise or if broken)
+    target: Option<fs::Metadata>,
+}
+
+impl ListedEntry {
+    fn new(name: String, path: PathBuf, metadata: fs::Metadata, config: &Config) -> Self {
+        let target = (config.link_sizes == LinkSizes::Target && metadata.file_type().is_symlink())
+            .then(|| fs::metadata(&path).ok())
+            .flatten();
+        Self {
+            name,
+            path,
+            metadata,
+            target,
+        }
+    }
+
+    /// Metadata that supplies the Size and Modified values: the symlink
+    /// target's for `--link-sizes target`, else the entry's own.
+    fn shown_metadata(&self) -> &fs::Metadata {
+        self.target.as_ref().unwrap_or(&self.metadata)
+    }
+}
 
 /// Displays directory entries in detailed table format.
 ///
@@ -23,6 +83,12 @@ use crate::formatting::format_size;
 ///
 /// * `entries` - Iterator over directory entries
 /// * `config` - Configuration specifying display options
+/// * `changes` - Changes since the previous run, for `--changed`
+/// * `out` - Destination for the table
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
 ///
 /// # Features
 ///
@@ -32,137 +98,1231 @@ use crate::formatting::format_size;
 /// - Optional clickable hyperlinks in interactive mode
 /// - Hidden file filtering based on configuration
 /// - Proper column alignment regardless of color codes
-pub fn display(entries: &[Result<fs::DirEntry, std::io::Error>], config: &Config) {
-    let mut file_infos = Vec::new();
+/// - Optional wrapping of long file names within the Name column
+/// - Right-aligned numeric columns with per-column alignment overrides
+/// - Optional summary row with the entry count and cumulative size
+/// - Optional compact style without borders between rows
+/// - Optional maximum layout width (`--width` or `COLUMNS`)
+/// - Optional recursive directory sizes in the Size column
+/// - Optional recursive inode counts in an Inodes column
+/// - Optional content hashes of files and directory trees in a Hash column
+/// - Optional Change column and removed-entry list relative to the previous run
+/// - Selectable timestamp columns (modified, accessed, changed, created, or all)
+/// - Optional Ext column and grouping by extension under separator rows
+/// - Optional compact Age column (time since modification)
+/// - Optional Allocated column highlighting sparse, compressed, and preallocated files
+/// - Optional preview of the first lines of small text files
+/// - Optional `.` and `..` rows (`--all-all`)
+/// - Optional alternating row shading (`--stripe`)
+/// - Optional symlink target sizes and times (`--link-sizes target`), marked with an arrow
+/// - Optional tab-separated, color-free output for diffable snapshots (`--deterministic`)
+/// - Optional CSV or TSV output with raw values for spreadsheets (`--format`)
+/// - Optional ordering by any column (`--sort-column`) with a header arrow
+/// - Optional blank owner cells for entries owned by the invoking user
+/// - Optional highlighting of permissions that deviate from an expected baseline
+pub fn display(
+    entries: &[Result<fs::DirEntry, std::io::Error>],
+    config: &Config,
+    changes: Option<&ChangeSet>,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    render(listed_entries(entries, config), config, changes, out)
+}
 
-    for entry in entries {
-        let Ok(entry) = entry else { continue };
+/// Writes a long listing entry by entry as the directory is read, for `--low-memory`.
+///
+/// Entries are written as tab-separated lines (like `--deterministic`), or
+/// as CSV with `--format csv`, in the order the filesystem returns them,
+/// since a drawn table needs every row to size its columns. Recursive sizes,
+/// inode counts, and hashes are computed one entry at a time on the calling
+/// thread, so memory use does not grow with the size of the directory.
+///
+/// # Arguments
+///
+/// * `entries` - The directory being read
+/// * `config` - Configuration specifying display options
+/// * `out` - Destination for the listing
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn display_streamed(entries: fs::ReadDir, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let visible_headers = visible_headers(&hidden_columns(config), config);
+    write_delimited_header(&visible_headers, config, out)?;
 
-        let file_name = entry.file_name();
-        let file_name_str = file_name.to_string_lossy();
+    let notes = config.comments.then(|| Notes::load(Path::new(&config.path)));
+    let dot_entries = super::dot_entries(config)
+        .into_iter()
+        .map(|(name, path, metadata)| Some(ListedEntry::new(name.to_string(), path, metadata, config)));
+    let entries = entries.map(|entry| match entry {
+        Ok(entry) => listed_entry(&entry, config),
+        Err(e) => {
+            warn!("cannot read directory entry: {}", e);
+            None
+        }
+    });
 
-        if !config.show_hidden && file_name_str.starts_with('.') {
-            continue;
+    for entry in dot_entries.chain(entries).flatten() {
+        let measured = entry.metadata.is_dir() && !is_dot_entry(&entry);
+        let mut directory_sizes = HashMap::new();
+        let mut directory_inodes = HashMap::new();
+        if measured && config.recursive_sizes {
+            directory_sizes.insert(entry.path.clone(), recursive_size(&entry.path));
+        }
+        if measured && config.inodes {
+            directory_inodes.insert(entry.path.clone(), recursive_inodes(&entry.path));
         }
 
-        let metadata = match entry.metadata() {
-            Ok(metadata) => metadata,
-            Err(_) => continue,
-        };
+        let mut file_info = entry_row(&entry, &directory_sizes, &directory_inodes, None, config);
+        if config.dir_hash && entry.name != ".." && !is_virtual(&entry.path) {
+            file_info.hash = content_hash(&entry.path)
+                .map_or_else(|_| "?".to_string(), |hash| hash_cell(hash, config));
+        }
+        if let Some(comment) = notes.as_ref().and_then(|notes| notes.comment(&entry.name, &entry.path)) {
+            file_info.comment = comment;
+        }
+        file_info.exec = config
+            .exec_columns
+            .iter()
+            .map(|column| exec_column::run(&column.command, &entry.path, config.exec_timeout))
+            .collect();
+        write_delimited_row(&file_info, &visible_headers, config, out)?;
+    }
+    Ok(())
+}
+
+/// Displays path arguments as one table, named as given on the command line.
+///
+/// This is used for file arguments, and for symlink arguments, which show the
+/// link itself rather than the directory it points to (unless `-H` is given).
+/// The paths are not filtered, since they were named explicitly.
+///
+/// # Arguments
+///
+/// * `paths` - The paths to display with their own metadata (from
+///   `symlink_metadata`), already sorted
+/// * `config` - Configuration specifying display options
+/// * `out` - Destination for the table
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn display_paths(paths: Vec<(PathBuf, fs::Metadata)>, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let listed = paths
+        .into_iter()
+        .map(|(path, metadata)| ListedEntry::new(path.display().to_string(), path, metadata, config))
+        .collect();
+    render(listed, config, None, out)
+}
+
+/// Builds, colors, and writes the table for the given entries.
+fn render(listed: Vec<ListedEntry>, config: &Config, changes: Option<&ChangeSet>, out: &mut dyn Write) -> io::Result<()> {
+    let directory_sizes = compute_directory_sizes(&listed, config);
+    let directory_inodes = compute_directory_inodes(&listed, config);
+    let hashes = compute_content_hashes(&listed, config);
+    let comments = compute_comments(&listed, config);
+    let paths: Vec<PathBuf> = listed.iter().map(|entry| entry.path.clone()).collect();
+    let mut exec_cells = exec_column::compute(&paths, &config.exec_columns, config.exec_timeout).into_iter();
+    let heat = compute_heat(&listed, &directory_sizes, config);
 
-        let file_info = FileInfo::from_metadata_with_path(file_name_str.to_string(), &metadata, &entry.path());
+    let mut rows: Vec<(ListedEntry, FileInfo)> = listed
+        .into_iter()
+        .map(|entry| {
+            let mut file_info = entry_row(&entry, &directory_sizes, &directory_inodes, changes, config);
+            if let Some(hash) = hashes.get(&entry.path) {
+                file_info.hash = hash.clone();
+            }
+            if let Some(comment) = comments.get(&entry.path) {
+                file_info.comment = comment.clone();
+            }
+            if let Some(bar) = heat.get(&entry.path) {
+                file_info.heat = bar.to_string();
+            }
+            file_info.exec = exec_cells.next().unwrap_or_default();
+            (entry, file_info)
+        })
+        .collect();
+    // Stable, so entries keep the --sort order within each group and sort key
+    rows.sort_by_cached_key(|(entry, file_info)| {
+        (
+            group_key(entry, config),
+            column_key(entry, file_info, &directory_sizes, &directory_inodes, changes, config),
+        )
+    });
+    let (listed, rows): (Vec<ListedEntry>, Vec<FileInfo>) = rows.into_iter().unzip();
+
+    let groups = group_sizes(&listed, config);
+    let mut group_labels = HashSet::new();
+    let mut file_infos = Vec::new();
+    let mut total_size = 0;
+    let mut total_inodes = 0;
+    let mut total_allocated = 0;
+    let mut type_counts: HashMap<String, usize> = HashMap::new();
+
+    for (index, (entry, mut file_info)) in listed.iter().zip(rows).enumerate() {
+        if let Some(key) = group_key(entry, config) {
+            if index == 0 || group_key(&listed[index - 1], config).as_ref() != Some(&key) {
+                let label = group_label(&key, groups[&key]);
+                group_labels.insert(label.clone());
+                if is_delimited(config) {
+                    file_infos.push(label_row(label));
+                } else {
+                    file_infos.push(label_row(label.blue().bold().to_string()));
+                }
+            }
+        }
+
+        *type_counts.entry(file_info.file_type.clone()).or_default() += 1;
+        if !is_delimited(config) {
+            color_row(&mut file_info, entry, &directory_sizes, config);
+        }
         file_infos.push(file_info);
+        total_size += entry_size(entry, &directory_sizes);
+        total_inodes += directory_inodes.get(&entry.path).copied().unwrap_or(1);
+        total_allocated += allocated_size(&entry.metadata);
     }
 
     if !file_infos.is_empty() {
-        let table = Table::new(file_infos).with(Style::modern()).to_string();
+        if config.summary_row {
+            let mut summary = summary_row(listed.len(), total_size, config);
+            if config.inodes {
+                summary.inodes = total_inodes.to_string();
+            }
+            if config.aggregates.contains(&Aggregate::Allocated) {
+                summary.allocated = format_entry_size(total_allocated, config);
+            }
+            if config.aggregates.contains(&Aggregate::Types) {
+                summary.file_type = type_counts_cell(type_counts);
+            }
+            file_infos.push(summary);
+        }
+
+        let hidden = hidden_columns(config);
+        let visible_headers = visible_headers(&hidden, config);
+
+        if config.deterministic || config.format.is_some() {
+            write_delimited(&file_infos, &visible_headers, config, out)?;
+        } else {
+            let exec_table = exec_table(&file_infos, config);
+            let mut table = Table::new(file_infos);
+            if let Some(exec_table) = exec_table {
+                table.with(Concat::horizontal(exec_table));
+            }
+            if config.compact {
+                // Only the header separator and outer frame, no lines between rows
+                table.with(Style::sharp());
+            } else {
+                table.with(Style::modern());
+            }
+
+            // Wrap long names inside their cell instead of widening the whole table
+            if let Some(wrap_width) = config.wrap_width {
+                table.modify(Columns::first(), Width::wrap(wrap_width).keep_words(true));
+            }
+
+            // Remove columns before aligning, since alignment is set by column position
+            for column in &hidden {
+                table.with(Remove::column(ByColumnName::new(column)));
+            }
+            for (index, header) in visible_headers.iter().enumerate() {
+                let alignment = match column_alignment(header, config) {
+                    ColumnAlignment::Left => Alignment::left(),
+                    ColumnAlignment::Right => Alignment::right(),
+                    ColumnAlignment::Center => Alignment::center(),
+                };
+                table.modify(Columns::one(index), alignment);
+            }
+
+            // Mark the --sort-column header with the direction of the order
+            if let Some(column) = config.sort_column.as_deref() {
+                if let Some(index) = visible_headers.iter().position(|header| header == column) {
+                    let arrow = sort_arrow(column);
+                    table.modify(Cell::new(0, index), Format::content(|header| format!("{} {}", header, arrow)));
+                }
+            }
 
-        // Apply colors after table is formatted
-        let colored_output = apply_colors_to_table(&table, entries, config);
-        println!("{}", colored_output);
+            // Shrink the widest columns first so the table fits the requested layout width
+            if let Some(width) = config.width {
+                table.with(Width::wrap(width).keep_words(true).priority(Priority::max(true)));
+            }
+
+            let table = table.to_string();
+            let striped = if config.stripe {
+                striped_lines(&table, &visible_headers, &group_labels)
+            } else {
+                HashSet::new()
+            };
+            writeln!(out, "{}", apply_stripes(&table, &striped))?;
+        }
     }
+
+    // Removed entries have no row, and a line below would break CSV and TSV files
+    if let Some(changes) = changes.filter(|_| config.format.is_none()) {
+        write_removed_entries(changes, config, out)?;
+    }
+    Ok(())
 }
 
-fn apply_colors_to_table(
-    table: &str,
-    entries: &[Result<fs::DirEntry, std::io::Error>],
-    config: &Config,
-) -> String {
-    let mut result = table.to_string();
+/// Builds a table of the `--exec-column` cells to append to the right of the
+/// built-in columns, or None if there are no such columns.
+///
+/// Rows without cells, such as group separators and the totals row, are left
+/// blank.
+fn exec_table(file_infos: &[FileInfo], config: &Config) -> Option<Table> {
+    if config.exec_columns.is_empty() {
+        return None;
+    }
 
-    // Collect all file names and sizes, sort by length (longest first) to avoid partial replacements
-    let mut file_entries = Vec::new();
-    let mut size_entries = Vec::new();
+    let mut builder = Builder::default();
+    builder.push_record(config.exec_columns.iter().map(|column| column.name.clone()));
+    for file_info in file_infos {
+        builder.push_record(exec_cells(file_info, config));
+    }
+    Some(builder.build())
+}
 
-    for entry in entries {
-        let Ok(entry) = entry else { continue };
-        let file_name = entry.file_name();
-        let file_name_str = file_name.to_string_lossy();
+/// Returns a row's `--exec-column` cells, blank where the row has none.
+fn exec_cells(file_info: &FileInfo, config: &Config) -> Vec<String> {
+    let mut cells = file_info.exec.clone();
+    cells.resize(config.exec_columns.len(), String::new());
+    cells
+}
 
-        if !config.show_hidden && file_name_str.starts_with('.') {
-            continue;
+/// Lists the entries removed since the previous run below the table.
+fn write_removed_entries(changes: &ChangeSet, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let removed: Vec<&str> = changes
+        .removed
+        .iter()
+        .filter(|name| filter::is_visible(name, config))
+        .map(String::as_str)
+        .collect();
+    if !removed.is_empty() {
+        writeln!(out, "{} {}", "Removed:".red().bold(), removed.join(", ").red())?;
+    }
+    Ok(())
+}
+
+/// Pairs readable entries with their metadata and keeps those passing the filters.
+///
+/// Entries whose metadata cannot be read are skipped, since the table has no
+/// values to show for them. With `--all-all`, `.` and `..` come first.
+fn listed_entries(entries: &[Result<fs::DirEntry, std::io::Error>], config: &Config) -> Vec<ListedEntry> {
+    let dot_entries = super::dot_entries(config)
+        .into_iter()
+        .map(|(name, path, metadata)| ListedEntry::new(name.to_string(), path, metadata, config));
+
+    let entries = entries
+        .iter()
+        .filter_map(|entry| entry.as_ref().ok())
+        .filter_map(|entry| listed_entry(entry, config));
+    dot_entries.chain(entries).collect()
+}
+
+/// Pairs a directory entry with its metadata if it passes the filters.
+///
+/// # Returns
+///
+/// The entry, or None if it is filtered out or its metadata cannot be read
+fn listed_entry(entry: &fs::DirEntry, config: &Config) -> Option<ListedEntry> {
+    let name = entry.file_name().to_string_lossy().to_string();
+    let metadata = match entry.metadata() {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            warn!("cannot read metadata of {}: {}", name, e);
+            return None;
         }
+    };
+    let entry = ListedEntry::new(name, entry.path(), metadata, config);
+    (filter::matches(&entry.name, &entry.metadata, config)
+        && filter::matches_contents(&entry.path, &entry.metadata, config))
+    .then_some(entry)
+}
 
-        if let Ok(metadata) = entry.metadata() {
-            let colored_name = get_colored_name(&file_name_str, &metadata);
-            if config.interactive {
-                let full_path = Path::new(&config.path).join(&file_name);
-                let clickable_name = make_clickable_link(&file_name_str, &full_path, &colored_name);
-                file_entries.push((file_name_str.to_string(), clickable_name));
-            } else {
-                file_entries.push((file_name_str.to_string(), colored_name));
-            }
+/// Computes recursive sizes for all listed directories when `--recursive-sizes` is on.
+///
+/// # Returns
+///
+/// A map from directory path to its recursive size in bytes (empty when disabled)
+fn compute_directory_sizes(listed: &[ListedEntry], config: &Config) -> HashMap<PathBuf, u64> {
+    if !config.recursive_sizes {
+        return HashMap::new();
+    }
+
+    let directories: Vec<PathBuf> = listed
+        .iter()
+        .filter(|entry| entry.metadata.is_dir() && !is_dot_entry(entry))
+        .map(|entry| entry.path.clone())
+        .collect();
+
+    let sizes = recursive_sizes(&directories);
+    directories.into_iter().zip(sizes).collect()
+}
+
+/// Counts the inodes below each listed directory when `--inodes` is requested.
+fn compute_directory_inodes(listed: &[ListedEntry], config: &Config) -> HashMap<PathBuf, u64> {
+    if !config.inodes {
+        return HashMap::new();
+    }
+
+    let directories: Vec<PathBuf> = listed
+        .iter()
+        .filter(|entry| entry.metadata.is_dir() && !is_dot_entry(entry))
+        .map(|entry| entry.path.clone())
+        .collect();
+
+    let counts = recursive_inode_counts(&directories);
+    directories.into_iter().zip(counts).collect()
+}
+
+/// Checks for the `.` and `..` pseudo-entries, whose recursive totals would
+/// cover the whole listing or its parent rather than an entry of it.
+fn is_dot_entry(entry: &ListedEntry) -> bool {
+    entry.name == "." || entry.name == ".."
+}
+
+/// Hashes the contents of every listed entry when `--dir-hash` is requested.
+///
+/// `..` is skipped, since hashing the parent would read far more than the
+/// listing; `.` is kept, as it hashes the listed tree as a whole. Entries on
+/// virtual filesystems such as `/proc` are skipped, since reading them can block.
+///
+/// # Returns
+///
+/// A map from entry path to its shortened hash, or `?` if it could not be read
+fn compute_content_hashes(listed: &[ListedEntry], config: &Config) -> HashMap<PathBuf, String> {
+    if !config.dir_hash {
+        return HashMap::new();
+    }
+
+    let paths: Vec<PathBuf> = listed
+        .iter()
+        .filter(|entry| entry.name != ".." && !is_virtual(&entry.path))
+        .map(|entry| entry.path.clone())
+        .collect();
+
+    let hashes = content_hashes(&paths);
+    paths
+        .into_iter()
+        .zip(hashes)
+        .map(|(path, hash)| {
+            let hash = hash.map_or_else(|| "?".to_string(), |hash| hash_cell(hash, config));
+            (path, hash)
+        })
+        .collect()
+}
+
+/// Returns the Hash cell: the shortened hash in the drawn table, and the full
+/// hash in delimited output, which scripts compare against other tools.
+fn hash_cell(hash: String, config: &Config) -> String {
+    if is_delimited(config) {
+        hash
+    } else {
+        hash[..SHORT_HASH_LEN].to_string()
+    }
+}
+
+/// Checks whether the listing is written as delimited lines
+/// (`--deterministic`, `--low-memory`, or `--format`) rather than as a drawn table.
+///
+/// Delimited output is meant for scripts and diffs, so it always carries
+/// complete values: it is never wrapped to `--wrap` or `--width`, and hashes
+/// and previews are not shortened.
+fn is_delimited(config: &Config) -> bool {
+    config.deterministic || config.low_memory || config.format.is_some()
+}
+
+/// Looks up the comment of every listed entry when `--comments` is requested.
+///
+/// The notes file of each directory is read once.
+///
+/// # Returns
+///
+/// A map from entry path to its comment, for entries that have one
+fn compute_comments(listed: &[ListedEntry], config: &Config) -> HashMap<PathBuf, String> {
+    if !config.comments {
+        return HashMap::new();
+    }
+
+    let mut notes: HashMap<PathBuf, Notes> = HashMap::new();
+    listed
+        .iter()
+        .filter_map(|entry| {
+            let directory = entry.path.parent().unwrap_or(Path::new(".")).to_path_buf();
+            let notes = notes.entry(directory).or_insert_with_key(|directory| Notes::load(directory));
+            // Path arguments are named as given, so look them up by file name
+            let name = entry.path.file_name().map_or(entry.name.clone(), |name| name.to_string_lossy().to_string());
+            let comment = notes.comment(&name, &entry.path)?;
+            Some((entry.path.clone(), comment))
+        })
+        .collect()
+}
+
+/// Bars of the Heat column, from coldest to hottest
+const HEAT_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Scales every listed entry's age or size to a heat bar for `--heat`.
+///
+/// Both are compared on a logarithmic scale, so one huge or ancient entry does
+/// not flatten all others to the same bar. The most recently modified (or
+/// largest) entry gets the tallest bar; when all values are equal, every
+/// entry gets the lowest one.
+///
+/// # Returns
+///
+/// A map from entry path to its bar, for entries whose value could be read
+fn compute_heat(listed: &[ListedEntry], directory_sizes: &HashMap<PathBuf, u64>, config: &Config) -> HashMap<PathBuf, char> {
+    let Some(heat) = config.heat else {
+        return HashMap::new();
+    };
+
+    let now = SystemTime::now();
+    let values: Vec<(&PathBuf, f64)> = listed
+        .iter()
+        .filter_map(|entry| {
+            let value = match heat {
+                HeatBy::Age => {
+                    let modified = entry.shown_metadata().modified().ok()?;
+                    let age = now.duration_since(modified).unwrap_or_default();
+                    -age.as_secs_f64().ln_1p()
+                }
+                HeatBy::Size => (entry_size(entry, directory_sizes) as f64).ln_1p(),
+            };
+            Some((&entry.path, value))
+        })
+        .collect();
+
+    let min = values.iter().map(|(_, value)| *value).fold(f64::INFINITY, f64::min);
+    let max = values.iter().map(|(_, value)| *value).fold(f64::NEG_INFINITY, f64::max);
+    let top = (HEAT_BARS.len() - 1) as f64;
+    values
+        .into_iter()
+        .map(|(path, value)| {
+            let level = if max > min { ((value - min) / (max - min) * top).round() as usize } else { 0 };
+            (path.clone(), HEAT_BARS[level])
+        })
+        .collect()
+}
+
+/// Returns the size to display for an entry: its recursive size if computed, else its own size
+/// (or its symlink target's with `--link-sizes target`).
+fn entry_size(entry: &ListedEntry, directory_sizes: &HashMap<PathBuf, u64>) -> u64 {
+    directory_sizes.get(&entry.path).copied().unwrap_or(entry.shown_metadata().len())
+}
+
+/// Marker in front of values taken from a symlink's target
+const TARGET_MARKER: &str = "→ ";
+
+/// Returns the marker in front of values taken from a symlink's target, or
+/// nothing with `--format`, whose cells hold raw values.
+fn target_marker(config: &Config) -> &'static str {
+    if config.format.is_some() {
+        ""
+    } else {
+        TARGET_MARKER
+    }
+}
+
+/// Formats the Size cell of an entry, marking sizes taken from a symlink's target.
+///
+/// Entries on virtual filesystems show the filesystem name instead, since their
+/// sizes are meaningless.
+fn size_text(entry: &ListedEntry, directory_sizes: &HashMap<PathBuf, u64>, config: &Config) -> String {
+    if let Some(filesystem) = entry_filesystem(&entry.path, &entry.metadata) {
+        return filesystem.to_string();
+    }
+    let size = format_entry_size(entry_size(entry, directory_sizes), config);
+    if entry.target.is_some() {
+        format!("{}{}", target_marker(config), size)
+    } else {
+        size
+    }
+}
+
+/// Formats the Δ Size cell of an entry that grew or shrank since the previous
+/// `--changed` run, e.g. "+1.5M" or "-300B".
+fn size_delta_text(delta: i64, config: &Config) -> String {
+    let sign = if delta < 0 { '-' } else { '+' };
+    format!("{}{}", sign, format_entry_size(delta.unsigned_abs(), config))
+}
+
+/// Splits a Size cell into its number and unit for `--size-split`.
+///
+/// Only sizes ending in a unit letter (such as "1.5K" or "→ 256B") are split;
+/// exact `--bytes` sizes and filesystem names stay whole, with an empty unit.
+///
+/// # Returns
+///
+/// The Size and Unit cells, e.g. ("1.5", "K")
+fn split_size(text: String, config: &Config) -> (String, String) {
+    let number = text.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    if !config.size_split || number.len() == text.len() || !number.ends_with(|c: char| c.is_ascii_digit()) {
+        return (text, String::new());
+    }
+    let unit = text[number.len()..].to_string();
+    (number.to_string(), unit)
+}
+
+/// Blanks the User and Group cells that match the invoking user for `--smart-owner`,
+/// so entries owned by someone else stand out.
+fn blank_own_owner(file_info: &mut FileInfo, metadata: &fs::Metadata) {
+    if metadata.uid() == users::get_effective_uid() {
+        file_info.user.clear();
+    }
+    if metadata.gid() == users::get_effective_gid() {
+        file_info.group.clear();
+    }
+}
+
+/// Builds the final table row holding the totals for `--summary-row`.
+///
+/// The entry count goes in the Items column and the cumulative size in the Size
+/// column so both line up with the per-file values above them; either is left
+/// empty when `--aggregates` does not select it.
+fn summary_row(entry_count: usize, total_size: u64, config: &Config) -> FileInfo {
+    let mut summary = label_row("Total".to_string());
+    if config.aggregates.contains(&Aggregate::Size) {
+        (summary.size, summary.size_unit) = split_size(format_entry_size(total_size, config), config);
+    }
+    if config.aggregates.contains(&Aggregate::Count) {
+        summary.item_count = entry_count.to_string();
+    }
+    summary
+}
+
+/// Formats the per-type entry counts of the summary row, most common type
+/// first, one type per line.
+fn type_counts_cell(type_counts: HashMap<String, usize>) -> String {
+    let mut type_counts: Vec<(String, usize)> = type_counts.into_iter().collect();
+    type_counts.sort_by(|(a_type, a_count), (b_type, b_count)| b_count.cmp(a_count).then_with(|| a_type.cmp(b_type)));
+    type_counts
+        .into_iter()
+        .map(|(file_type, count)| format!("{}: {}", file_type, count))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes the rows as delimited lines for `--deterministic` and `--format`.
+///
+/// Unlike the drawn table, the lines do not depend on the widest value in each
+/// column, so a change to one entry changes one line of a stored snapshot.
+fn write_delimited(
+    file_infos: &[FileInfo],
+    visible_headers: &[String],
+    config: &Config,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    write_delimited_header(visible_headers, config, out)?;
+    for file_info in file_infos {
+        write_delimited_row(file_info, visible_headers, config, out)?;
+    }
+    Ok(())
+}
 
-            // Also collect size information for coloring
-            let size = metadata.len();
-            let size_str = format_size(size);
-            let colored_size = get_colored_size(&size_str, size);
-            size_entries.push((size_str, colored_size));
+/// Writes the header line of delimited output.
+///
+/// The `--sort-column` arrow is left out of `--format` headers, which name
+/// the fields for spreadsheets and data pipelines.
+fn write_delimited_header(visible_headers: &[String], config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let header_cells: Vec<String> = visible_headers
+        .iter()
+        .map(|header| match config.sort_column.as_deref() {
+            Some(column) if column == header && config.format.is_none() => format!("{} {}", header, sort_arrow(column)),
+            _ => header.clone(),
+        })
+        .map(|header| delimited_cell(header, config))
+        .collect();
+    writeln!(out, "{}", header_cells.join(delimiter(config)))
+}
+
+/// Writes the visible cells of one row as a delimited line.
+fn write_delimited_row(
+    file_info: &FileInfo,
+    visible_headers: &[String],
+    config: &Config,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let headers = FileInfo::headers();
+    let fields = file_info.fields();
+    let cells: Vec<String> = visible_headers
+        .iter()
+        .filter_map(|visible| headers.iter().position(|header| header == visible))
+        .map(|index| fields[index].to_string())
+        .chain(exec_cells(file_info, config))
+        .map(|cell| delimited_cell(cell, config))
+        .collect();
+    writeln!(out, "{}", cells.join(delimiter(config)))
+}
+
+/// Returns the separator between the cells of delimited output.
+fn delimiter(config: &Config) -> &'static str {
+    match config.format {
+        Some(DelimitedFormat::Csv) => ",",
+        Some(DelimitedFormat::Tsv) | None => "\t",
+    }
+}
+
+/// Escapes one cell of delimited output.
+///
+/// CSV cells containing commas, quotes, or line breaks are quoted, with quotes
+/// doubled (RFC 4180). Tab-separated output has no quoting, so tabs and line
+/// breaks inside values are replaced with spaces.
+fn delimited_cell(cell: String, config: &Config) -> String {
+    if config.format != Some(DelimitedFormat::Csv) {
+        return cell.replace(['\t', '\n'], " ");
+    }
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell
+    }
+}
+
+/// Builds a row with only the Name cell filled, for totals and group separators.
+fn label_row(name: String) -> FileInfo {
+    FileInfo {
+        name,
+        new: String::new(),
+        inode: String::new(),
+        file_type: String::new(),
+        ext: String::new(),
+        user_perms: String::new(),
+        group_perms: String::new(),
+        other_perms: String::new(),
+        octal: String::new(),
+        user: String::new(),
+        group: String::new(),
+        size: String::new(),
+        size_unit: String::new(),
+        size_delta: String::new(),
+        allocated: String::new(),
+        modified: String::new(),
+        accessed: String::new(),
+        changed: String::new(),
+        created: String::new(),
+        age: String::new(),
+        heat: String::new(),
+        item_count: String::new(),
+        inodes: String::new(),
+        hash: String::new(),
+        change: String::new(),
+        comment: String::new(),
+        preview: String::new(),
+        exec: Vec::new(),
+        link_target: None,
+        broken_link: false,
+    }
+}
+
+/// Builds the table row of one entry.
+fn entry_row(
+    entry: &ListedEntry,
+    directory_sizes: &HashMap<PathBuf, u64>,
+    directory_inodes: &HashMap<PathBuf, u64>,
+    changes: Option<&ChangeSet>,
+    config: &Config,
+) -> FileInfo {
+    let inodes = directory_inodes.get(&entry.path).copied().unwrap_or(1);
+    let mut file_info = FileInfo::from_metadata_with_path(entry.name.clone(), &entry.metadata, &entry.path, config);
+    file_info.name = name_cell(entry, config);
+    (file_info.size, file_info.size_unit) = split_size(size_text(entry, directory_sizes, config), config);
+    if let Some(target) = &entry.target {
+        file_info.modified = format!("{}{}", target_marker(config), format_listing_time(target.modified().ok(), config));
+    }
+    file_info.inodes = inodes.to_string();
+    if let Some(description) = changes.and_then(|changes| changes.describe(&entry.name)) {
+        file_info.change = description;
+    }
+    if let Some(&delta) = changes.and_then(|changes| changes.size_deltas.get(&entry.name)) {
+        file_info.size_delta = size_delta_text(delta, config);
+    }
+    if config.smart_owner {
+        blank_own_owner(&mut file_info, &entry.metadata);
+    }
+    if let Some(lines) = config.preview {
+        file_info.preview = preview_lines(&entry.path, &entry.metadata, lines, !is_delimited(config))
+            .map(|lines| lines.join("\n"))
+            .unwrap_or_default();
+    }
+    file_info
+}
+
+/// Returns the Name cell of an entry: its icon with `--icons` and the badges
+/// of failed name checks (neither in tab-separated output), and for a symlink
+/// `name -> target` as in `ls -l`.
+fn name_cell(entry: &ListedEntry, config: &Config) -> String {
+    let link = link_target(&entry.path, &entry.metadata);
+    // The checks run either way, so they are reported and set the exit status
+    let badges = check_badges(&entry.path, config);
+    let (icon, badges) = if is_delimited(config) {
+        (String::new(), String::new())
+    } else {
+        let broken = link.as_ref().is_some_and(|(_, broken)| *broken);
+        (icon_prefix(&entry.name, &get_file_type(&entry.metadata), broken, config), badges)
+    };
+    match link {
+        Some((target, _)) => format!("{}{} {} {}{}", icon, entry.name, LINK_ARROW, target, badges),
+        None => format!("{}{}{}", icon, entry.name, badges),
+    }
+}
+
+/// Sort key of a row for `--sort-column`.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum ColumnKey {
+    None,
+    /// Ascending; values that do not apply (such as Items of a file) sort first
+    Number(Option<u64>),
+    /// Ascending, so shrinking entries come first; unchanged sizes sort as 0
+    Signed(i64),
+    /// Newest first; entries without the timestamp sort last
+    Time(Reverse<Option<SystemTime>>),
+    Text(String),
+}
+
+/// Returns the header arrow of a `--sort-column` column: `▼` for timestamps,
+/// which are listed newest first, and `▲` for everything else, which ascends.
+fn sort_arrow(column: &str) -> &'static str {
+    if matches!(column, "Modified" | "Accessed" | "Changed" | "Created") {
+        "▼"
+    } else {
+        "▲"
+    }
+}
+
+/// Computes the `--sort-column` key of a row.
+///
+/// Numeric and time columns are compared by their underlying values rather
+/// than their formatted text, so `2.0K` sorts after `512B`. Age sorts
+/// youngest first, which is ascending by age.
+fn column_key(
+    entry: &ListedEntry,
+    file_info: &FileInfo,
+    directory_sizes: &HashMap<PathBuf, u64>,
+    directory_inodes: &HashMap<PathBuf, u64>,
+    changes: Option<&ChangeSet>,
+    config: &Config,
+) -> ColumnKey {
+    let Some(column) = config.sort_column.as_deref() else {
+        return ColumnKey::None;
+    };
+    let metadata = &entry.metadata;
+
+    match column {
+        // The Name cell also holds the icon, badges, and symlink target
+        "Name" => ColumnKey::Text(entry.name.clone()),
+        "Size" | "Unit" => ColumnKey::Number(Some(entry_size(entry, directory_sizes))),
+        "Allocated" => ColumnKey::Number(Some(allocated_size(metadata))),
+        "Octal" => ColumnKey::Number(Some(u64::from(metadata.permissions().mode() & 0o7777))),
+        "Inode" => ColumnKey::Number(Some(metadata.ino())),
+        "Inodes" => ColumnKey::Number(Some(directory_inodes.get(&entry.path).copied().unwrap_or(1))),
+        "Items" => ColumnKey::Number(file_info.item_count.parse().ok()),
+        "Δ Size" => ColumnKey::Signed(
+            changes
+                .and_then(|changes| changes.size_deltas.get(&entry.name))
+                .copied()
+                .unwrap_or_default(),
+        ),
+        "Modified" => ColumnKey::Time(Reverse(entry.shown_metadata().modified().ok())),
+        "Age" => ColumnKey::Time(Reverse(metadata.modified().ok())),
+        "Accessed" => ColumnKey::Time(Reverse(metadata.accessed().ok())),
+        "Changed" => ColumnKey::Time(Reverse(changed_time(metadata))),
+        "Created" => ColumnKey::Time(Reverse(metadata.created().ok())),
+        _ => {
+            let index = FileInfo::headers().iter().position(|header| header == column);
+            let fields = file_info.fields();
+            ColumnKey::Text(index.map(|index| fields[index].to_string()).unwrap_or_default())
         }
     }
+}
+
+/// Returns the grouping key of an entry for `--group-by`.
+///
+/// Extensions are compared case-insensitively, so `photo.JPG` and `photo.jpg`
+/// end up in the same group.
+fn group_key(entry: &ListedEntry, config: &Config) -> Option<String> {
+    match config.group_by? {
+        GroupBy::Ext => Some(file_extension(&entry.path, &entry.metadata).to_lowercase()),
+    }
+}
 
-    // Sort by filename length (longest first) to avoid partial matches
-    file_entries.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
-    size_entries.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+/// Counts the entries of each group for the separator rows.
+fn group_sizes(listed: &[ListedEntry], config: &Config) -> HashMap<String, usize> {
+    let mut sizes = HashMap::new();
+    for key in listed.iter().filter_map(|entry| group_key(entry, config)) {
+        *sizes.entry(key).or_insert(0) += 1;
+    }
+    sizes
+}
 
-    // Apply replacements
-    result = apply_file_name_colors(result, file_entries);
-    result = apply_size_colors(result, size_entries);
+/// Returns the Name cell of the separator row that starts a group.
+fn group_label(key: &str, count: usize) -> String {
+    let noun = if count == 1 { "entry" } else { "entries" };
+    if key.is_empty() {
+        format!("no extension ({} {})", count, noun)
+    } else {
+        format!("*.{} ({} {})", key, count, noun)
+    }
+}
 
-    result
+/// Returns the headers of the columns the table would show, in order.
+///
+/// # Arguments
+///
+/// * `config` - Configuration specifying the shown columns
+///
+/// # Returns
+///
+/// The visible column headers, including `--exec-column` columns
+pub fn columns(config: &Config) -> Vec<String> {
+    visible_headers(&hidden_columns(config), config)
 }
 
-fn apply_file_name_colors(mut result: String, file_entries: Vec<(String, String)>) -> String {
-    for (file_name, colored_name) in file_entries {
-        let lines: Vec<&str> = result.split('\n').collect();
-        let mut new_lines = Vec::new();
+/// Returns the headers of the columns that are shown, in table order.
+///
+/// The `--exec-column` columns come last, in the order they were given.
+fn visible_headers(hidden: &[String], config: &Config) -> Vec<String> {
+    FileInfo::headers()
+        .into_iter()
+        .filter(|header| !hidden.iter().any(|column| column == header))
+        .map(|header| header.to_string())
+        .chain(config.exec_columns.iter().map(|column| column.name.clone()))
+        .collect()
+}
 
-        for line in lines {
-            // Only replace if it's the actual filename in the first column with exact boundary
-            let filename_pattern = format!("│ {} ", file_name);
-            if line.contains(&filename_pattern) {
-                let new_line = line.replace(&filename_pattern, &format!("│ {} ", colored_name));
-                new_lines.push(new_line);
-            } else {
-                new_lines.push(line.to_string());
-            }
+/// Columns shown by default that a `--preset` hides unless it lists them
+const PRESET_BASE_COLUMNS: &[&str] = &[
+    "Type",
+    "User Permission",
+    "Group Permission",
+    "Other Permission",
+    "Octal",
+    "User",
+    "Group",
+    "Size",
+    "Items",
+];
+
+/// Returns the headers of optional columns that were not requested.
+///
+/// Only the timestamp columns selected with `--times` are shown. The Ext,
+/// Allocated, Age, Heat, New, Preview, Inodes, Hash, Change, and Comment
+/// columns are shown only with `--ext`, `--disk-usage-delta`, `--age`,
+/// `--heat`, `--highlight-new`, `--preview`, `--inodes`, `--dir-hash`,
+/// `--changed`, and `--comments` (or a `--preset` that lists them), and the
+/// Δ Size column only with `--changed` too. The Inode column is shown only
+/// with such a preset. A preset also hides the default columns it does not
+/// list, and columns named with `--hide` are never shown. The Unit column is
+/// shown with `--size-split` whenever the Size column is.
+fn hidden_columns(config: &Config) -> Vec<String> {
+    let time_columns = [
+        (TimeColumns::Modified, "Modified"),
+        (TimeColumns::Accessed, "Accessed"),
+        (TimeColumns::Changed, "Changed"),
+        (TimeColumns::Created, "Created"),
+    ];
+
+    let mut hidden: Vec<String> = time_columns
+        .into_iter()
+        .filter(|(column, _)| config.times != TimeColumns::All && config.times != *column)
+        .map(|(_, header)| header.to_string())
+        .collect();
+    if config.preview.is_none() {
+        hidden.push("Preview".to_string());
+    }
+    if !config.show_ext {
+        hidden.push("Ext".to_string());
+    }
+    let allocated_total = config.summary_row && config.aggregates.contains(&Aggregate::Allocated);
+    if !config.disk_usage_delta && !allocated_total {
+        hidden.push("Allocated".to_string());
+    }
+    if !config.show_age {
+        hidden.push("Age".to_string());
+    }
+    if config.heat.is_none() {
+        hidden.push("Heat".to_string());
+    }
+    if config.highlight_new.is_none() {
+        hidden.push("New".to_string());
+    }
+    if !config.inode_numbers {
+        hidden.push("Inode".to_string());
+    }
+    if let Some(preset) = config.preset {
+        hidden.extend(
+            PRESET_BASE_COLUMNS
+                .iter()
+                .filter(|column| !preset.shows(column))
+                .map(|column| column.to_string()),
+        );
+    }
+    if !config.inodes {
+        hidden.push("Inodes".to_string());
+    }
+    if !config.dir_hash {
+        hidden.push("Hash".to_string());
+    }
+    if !config.changed {
+        hidden.push("Change".to_string());
+        hidden.push("Δ Size".to_string());
+    }
+    if !config.comments {
+        hidden.push("Comment".to_string());
+    }
+    hidden.extend(
+        FileInfo::headers()
+            .into_iter()
+            .filter(|header| config.hidden_columns.iter().any(|column| column.eq_ignore_ascii_case(header)))
+            .map(|header| header.to_string()),
+    );
+    // The unit belongs to the Size column and goes wherever it goes
+    if !config.size_split || hidden.iter().any(|column| column == "Size") {
+        hidden.push("Unit".to_string());
+    }
+    hidden
+}
+
+/// Numeric columns that are right-aligned unless overridden with `--align`.
+const RIGHT_ALIGNED_COLUMNS: &[&str] = &["Inode", "Octal", "Size", "Δ Size", "Allocated", "Age", "Inodes"];
+
+/// Resolves the alignment of a column from `--align` overrides or the defaults.
+///
+/// Overrides match column headers case-insensitively, and the last matching
+/// override wins so later flags can refine earlier ones.
+fn column_alignment(header: &str, config: &Config) -> ColumnAlignment {
+    config
+        .column_alignments
+        .iter()
+        .rev()
+        .find(|(column, _)| column.eq_ignore_ascii_case(header))
+        .map(|(_, alignment)| *alignment)
+        .unwrap_or(if RIGHT_ALIGNED_COLUMNS.contains(&header) {
+            ColumnAlignment::Right
+        } else {
+            ColumnAlignment::Left
+        })
+}
+
+/// Colors the cells of an entry's row for the drawn table.
+///
+/// Cells are colored before the table is built, each by its own entry, so
+/// values that repeat across rows or columns cannot be mixed up. The table
+/// measures cells without their escape sequences, so colors and hyperlinks do
+/// not affect alignment or wrapping. This runs after `--sort-column` has
+/// compared the plain cell text.
+fn color_row(file_info: &mut FileInfo, entry: &ListedEntry, directory_sizes: &HashMap<PathBuf, u64>, config: &Config) {
+    file_info.name = colored_name_cell(entry, config);
+    if !file_info.size.is_empty() {
+        file_info.size = get_colored_size(&file_info.size, entry_size(entry, directory_sizes), &config.size_thresholds);
+    }
+    if config.highlight_new.is_some() && !file_info.new.is_empty() {
+        file_info.new = file_info.new.green().bold().to_string();
+    }
+    if config.changed {
+        file_info.change = change_color(&file_info.change);
+        file_info.size_delta = size_delta_color(&file_info.size_delta);
+    }
+    if config.heat.is_some() {
+        file_info.heat = heat_color(&file_info.heat);
+    }
+    if config.disk_usage_delta && has_usage_delta(&entry.metadata) {
+        file_info.allocated = if allocated_size(&entry.metadata) < entry.metadata.len() {
+            file_info.allocated.cyan().bold().to_string()
+        } else {
+            file_info.allocated.yellow().bold().to_string()
+        };
+    }
+
+    let orphaned = orphaned_owners(&entry.path, config);
+    if orphaned.contains(&"unknown user") {
+        file_info.user = file_info.user.red().bold().to_string();
+    }
+    if orphaned.contains(&"unknown group") {
+        file_info.group = file_info.group.red().bold().to_string();
+    }
+
+    let unusual = config.highlight_permissions && has_unusual_permissions(&entry.metadata, config);
+    let explanation = config.interactive.then(|| explain_permissions(&entry.metadata));
+    for cell in [
+        &mut file_info.user_perms,
+        &mut file_info.group_perms,
+        &mut file_info.other_perms,
+        &mut file_info.octal,
+    ] {
+        if unusual {
+            *cell = cell.yellow().bold().to_string();
         }
+        // Link to a plain-words explanation of the mode, shown as a tooltip by
+        // terminals that preview link URLs
+        if let Some(explanation) = &explanation {
+            *cell = make_explanation_link(explanation, cell);
+        }
+    }
+}
 
-        result = new_lines.join("\n");
+/// Returns the colored Name cell of an entry for the drawn table: its icon,
+/// its name colored by type (a hyperlink with `-i`), the ` -> target` of a
+/// symlink, and the badges of failed name checks.
+fn colored_name_cell(entry: &ListedEntry, config: &Config) -> String {
+    let mut colored_name = get_colored_name(&entry.name, &entry.path, &entry.metadata);
+    if config.interactive {
+        colored_name = make_clickable_link(&entry.name, &entry.path, &colored_name);
     }
-    result
+    let link = link_target(&entry.path, &entry.metadata);
+    let broken = link.as_ref().is_some_and(|(_, broken)| *broken);
+    let icon = icon_prefix(&entry.name, &get_file_type(&entry.metadata), broken, config);
+    let suffix = link.map(|(target, broken)| link_suffix(&target, broken)).unwrap_or_default();
+    let badges = check_badges(&entry.path, config);
+    let badges = if badges.is_empty() { badges } else { badges.red().bold().to_string() };
+    format!("{}{}{}{}", icon, colored_name, suffix, badges)
 }
 
-fn apply_size_colors(mut result: String, size_entries: Vec<(String, String)>) -> String {
-    for (size_str, colored_size) in size_entries {
-        let lines: Vec<&str> = result.split('\n').collect();
-        let mut new_lines = Vec::new();
+/// Checks whether an entry's permissions deviate from the expected baseline.
+///
+/// Permissions are unusual when they grant more than the expected mode (e.g.
+/// a world-writable 777 directory or 666 file), when an execute bit is set
+/// without the matching read bit, or when a file is setuid/setgid. Executables
+/// may have execute wherever the file baseline has read. Symlinks are ignored,
+/// since their own permissions are not used.
+fn has_unusual_permissions(metadata: &fs::Metadata, config: &Config) -> bool {
+    if metadata.file_type().is_symlink() {
+        return false;
+    }
 
-        for line in lines {
-            if line.contains(&size_str) {
-                // Replace size ensuring we don't replace partial matches
-                let size_pattern = format!(" {} ", size_str);
-                let colored_pattern = format!(" {} ", colored_size);
-                if line.contains(&size_pattern) {
-                    let new_line = line.replace(&size_pattern, &colored_pattern);
-                    new_lines.push(new_line);
-                } else {
-                    // Check for size at end of cell (before │)
-                    let size_pattern_end = format!(" {} │", size_str);
-                    let colored_pattern_end = format!(" {} │", colored_size);
-                    if line.contains(&size_pattern_end) {
-                        let new_line = line.replace(&size_pattern_end, &colored_pattern_end);
-                        new_lines.push(new_line);
-                    } else {
-                        new_lines.push(line.to_string());
-                    }
-                }
-            } else {
-                new_lines.push(line.to_string());
-            }
+    let mode = metadata.permissions().mode();
+    let permissions = mode & 0o777;
+    if metadata.is_dir() {
+        return permissions & !config.expected_dir_mode != 0;
+    }
+
+    let expected = if is_executable(metadata) {
+        config.expected_file_mode | (config.expected_file_mode & 0o444) >> 2
+    } else {
+        config.expected_file_mode
+    };
+    let executable_without_read = [0o100, 0o010, 0o001]
+        .into_iter()
+        .any(|execute| mode & execute != 0 && mode & (execute << 2) == 0);
+
+    permissions & !expected != 0 || executable_without_read || mode & 0o6000 != 0
+}
+
+/// Smallest difference between apparent and allocated size that is highlighted
+const USAGE_DELTA_MIN_BYTES: u64 = 64 * 1024;
+
+/// Checks whether a file's allocated size differs significantly from its apparent size.
+///
+/// Sparse files and transparently compressed files allocate much less than
+/// their apparent size, preallocated files much more. Differences below
+/// [`USAGE_DELTA_MIN_BYTES`] or a quarter of the larger size are ordinary
+/// block rounding. Only regular files are considered.
+fn has_usage_delta(metadata: &fs::Metadata) -> bool {
+    if !metadata.is_file() {
+        return false;
+    }
+
+    let (apparent, allocated) = (metadata.len(), allocated_size(metadata));
+    let delta = apparent.abs_diff(allocated);
+    delta >= USAGE_DELTA_MIN_BYTES && delta >= apparent.max(allocated) / 4
+}
+
+/// Colors a Heat cell for `--heat`: low bars blue, middle yellow, high red.
+fn heat_color(text: &str) -> String {
+    match HEAT_BARS.iter().position(|bar| text.starts_with(*bar)) {
+        Some(0..=2) => text.blue().to_string(),
+        Some(3..=5) => text.yellow().to_string(),
+        Some(_) => text.red().to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// Colors a Δ Size cell for `--changed`: growth green, shrinkage red.
+fn size_delta_color(text: &str) -> String {
+    match text.chars().next() {
+        Some('+') => text.green().to_string(),
+        Some('-') => text.red().to_string(),
+        _ => text.to_string(),
+    }
+}
+
+/// Colors a Change cell for `--changed`: new entries green, modified yellow,
+/// and ownership or permission drift magenta.
+fn change_color(text: &str) -> String {
+    match text {
+        "" => String::new(),
+        "new" => text.green().bold().to_string(),
+        "modified" => text.yellow().to_string(),
+        _ => text.magenta().bold().to_string(),
+    }
+}
+
+/// Background of striped rows (a dark gray from the 256-color palette)
+const STRIPE_BACKGROUND: &str = "\x1b[48;5;236m";
+
+/// Columns whose long values wrap onto continuation lines of the same row
+const WRAPPING_COLUMNS: &[&str] = &["Name", "Preview"];
+
+/// Returns the indexes of the table lines that belong to every second data row.
+///
+/// A line continues the previous row when only wrapping columns have text,
+/// which keeps wrapped names and multi-line previews on one stripe in the
+/// compact style too. Striping restarts after each `--group-by` separator row.
+fn striped_lines(table: &str, visible_headers: &[String], group_labels: &HashSet<String>) -> HashSet<usize> {
+    let mut striped = HashSet::new();
+    let mut in_header = true;
+    let mut row = 0;
+    let mut stripe = false;
+
+    for (index, line) in table.lines().enumerate() {
+        let Some(rest) = line.strip_prefix('│') else {
+            // The first horizontal rule below the top border ends the header
+            in_header &= !line.starts_with('├');
+            continue;
+        };
+        if in_header {
+            continue;
         }
 
-        result = new_lines.join("\n");
+        let cells: Vec<String> = rest.split('│').map(|cell| strip_escapes(cell).trim().to_string()).collect();
+        if group_labels.contains(&cells[0]) {
+            row = 0;
+            continue;
+        }
+
+        let starts_row = cells.iter().zip(visible_headers).any(|(cell, header)| {
+            !cell.is_empty() && !WRAPPING_COLUMNS.contains(&header.as_str())
+        });
+        if starts_row {
+            row += 1;
+            stripe = row % 2 == 0;
+        }
+        if stripe {
+            striped.insert(index);
+        }
+    }
+    striped
+}
+
+/// Shades the given lines of the colored table for `--stripe`.
+///
+/// The background is restored after every reset inside the line, so colored
+/// cells keep the stripe. Nothing changes when colors are disabled.
+fn apply_stripes(table: &str, striped: &HashSet<usize>) -> String {
+    if striped.is_empty() || !colored::control::SHOULD_COLORIZE.should_colorize() {
+        return table.to_string();
     }
-    result
-}
\ No newline at end of file
+
+    table
+        .split('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            if striped.contains(&index) {
+                let reset = "\x1b[0m";
+                let shaded = line.replace(reset, &format!("{}{}", reset, STRIPE_BACKGROUND));
+                format!("{}{}{}", STRIPE_BACKGROUND, shaded, reset)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,196 @@
+//! Trash listing implementation.
+//!
+//! This module provides the `fls trash` view: a table of the entries in the
+//! user's trash with their original path, deletion date, and size, so it is
+//! easy to see what emptying the trash would reclaim. The freedesktop.org
+//! home trash (`$XDG_DATA_HOME/Trash`) is read on Linux and other freedesktop
+//! systems, and `~/.Trash` on macOS.
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+use colored::*;
+use percent_encoding::percent_decode_str;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tabled::{builder::Builder, settings::Style};
+
+use crate::config::Config;
+use crate::dir_size::recursive_sizes;
+use crate::file_info::{changed_time, format_entry_size, format_listing_time};
+
+/// One entry in the trash.
+struct TrashEntry {
+    /// Name of the entry inside the trash
+    name: String,
+    /// Where the entry lived before it was trashed, if recorded
+    original_path: Option<PathBuf>,
+    /// When the entry was trashed, if recorded
+    deleted: Option<SystemTime>,
+    /// Path of the entry inside the trash
+    path: PathBuf,
+}
+
+/// Displays the entries in the trash as a table, most recently deleted first.
+///
+/// Directory sizes are computed recursively, and the total reclaimable size is
+/// shown below the table.
+///
+/// # Arguments
+///
+/// * `config` - Configuration specifying display options
+///
+/// # Errors
+///
+/// Prints an error message to stderr if no trash directory can be found.
+pub fn display(config: &Config) {
+    let Some(entries) = trash_entries() else {
+        eprintln!(
+            "{}: cannot find a trash directory (set XDG_DATA_HOME or HOME)",
+            "Error".red().bold()
+        );
+        return;
+    };
+
+    let mut entries: Vec<TrashEntry> = entries
+        .into_iter()
+        .filter(|entry| config.show_hidden || !entry.name.starts_with('.'))
+        .collect();
+    entries.sort_by(|a, b| b.deleted.cmp(&a.deleted).then_with(|| a.name.cmp(&b.name)));
+
+    if entries.is_empty() {
+        println!("The trash is empty");
+        return;
+    }
+
+    let metadata: Vec<Option<fs::Metadata>> = entries.iter().map(|entry| fs::symlink_metadata(&entry.path).ok()).collect();
+    let directories: Vec<PathBuf> = entries
+        .iter()
+        .zip(&metadata)
+        .filter(|(_, metadata)| metadata.as_ref().is_some_and(fs::Metadata::is_dir))
+        .map(|(entry, _)| entry.path.clone())
+        .collect();
+    let mut directory_sizes = recursive_sizes(&directories).into_iter();
+    let sizes: Vec<u64> = metadata
+        .iter()
+        .map(|metadata| match metadata {
+            Some(metadata) if metadata.is_dir() => directory_sizes.next().unwrap_or(0),
+            Some(metadata) => metadata.len(),
+            None => 0,
+        })
+        .collect();
+
+    let mut builder = Builder::default();
+    builder.push_record(["Name", "Original Path", "Deleted", "Size"]);
+    for (entry, size) in entries.iter().zip(&sizes) {
+        let original_path = entry
+            .original_path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+        builder.push_record([
+            entry.name.clone(),
+            original_path,
+            format_listing_time(entry.deleted, config),
+            format_entry_size(*size, config),
+        ]);
+    }
+
+    println!("{}", builder.build().with(Style::modern()));
+    println!(
+        "{} {} in {} {}",
+        "Reclaimable:".bold(),
+        format_entry_size(sizes.iter().sum(), config),
+        entries.len(),
+        if entries.len() == 1 { "entry" } else { "entries" }
+    );
+}
+
+/// Reads the entries of the freedesktop.org home trash, or of the macOS trash
+/// if there is none.
+///
+/// Returns None if neither trash directory can be located.
+fn trash_entries() -> Option<Vec<TrashEntry>> {
+    let home = std::env::var_os("HOME").filter(|value| !value.is_empty()).map(PathBuf::from);
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".local").join("share")));
+
+    if let Some(trash) = data_home.map(|data_home| data_home.join("Trash")) {
+        if trash.is_dir() || home.as_ref().is_none_or(|home| !home.join(".Trash").is_dir()) {
+            return Some(freedesktop_entries(&trash));
+        }
+    }
+    home.map(|home| macos_entries(&home.join(".Trash")))
+}
+
+/// Reads a freedesktop.org trash: the entries in `files/`, described by the
+/// `.trashinfo` files of the same name in `info/`.
+fn freedesktop_entries(trash: &Path) -> Vec<TrashEntry> {
+    let Ok(files) = fs::read_dir(trash.join("files")) else {
+        return Vec::new();
+    };
+
+    files
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let info_file = trash.join("info").join(format!("{}.trashinfo", name));
+            let (original_path, deleted) = fs::read_to_string(info_file)
+                .map(|contents| parse_trash_info(&contents, trash))
+                .unwrap_or_default();
+            TrashEntry {
+                name,
+                original_path,
+                deleted,
+                path: entry.path(),
+            }
+        })
+        .collect()
+}
+
+/// Parses the `Path` and `DeletionDate` keys of a `.trashinfo` file.
+///
+/// Relative paths are relative to the directory containing the trash, and the
+/// deletion date is in local time without a time zone.
+fn parse_trash_info(contents: &str, trash: &Path) -> (Option<PathBuf>, Option<SystemTime>) {
+    let mut original_path = None;
+    let mut deleted = None;
+    for line in contents.lines() {
+        if let Some(value) = line.strip_prefix("Path=") {
+            let path = PathBuf::from(percent_decode_str(value).decode_utf8_lossy().to_string());
+            original_path = Some(match trash.parent() {
+                Some(top) if path.is_relative() => top.join(path),
+                _ => path,
+            });
+        } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+            deleted = NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%dT%H:%M:%S")
+                .ok()
+                .and_then(|date| Local.from_local_datetime(&date).earliest())
+                .map(SystemTime::from);
+        }
+    }
+    (original_path, deleted)
+}
+
+/// Reads the macOS trash.
+///
+/// Finder keeps the original locations in a private format, so they are
+/// unknown, and the status change time of each entry (when it was moved into
+/// the trash) stands in for the deletion date.
+fn macos_entries(trash: &Path) -> Vec<TrashEntry> {
+    let Ok(files) = fs::read_dir(trash) else {
+        return Vec::new();
+    };
+
+    files
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name() != ".DS_Store")
+        .map(|entry| TrashEntry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            original_path: None,
+            deleted: entry.metadata().ok().and_then(|metadata| changed_time(&metadata)),
+            path: entry.path(),
+        })
+        .collect()
+}
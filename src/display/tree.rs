@@ -5,23 +5,27 @@
 
 use colored::*;
 use std::fs::{self, DirEntry};
-use std::io::Result as IoResult;
-use std::path::Path;
+use std::io::{Result as IoResult, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
 
-use crate::colors::format_with_color;
-use crate::config::Config;
+use crate::checks::check_badges;
+use crate::colors::{format_with_color, link_suffix, make_clickable_link, make_listing_link};
+use crate::config::{Config, DirLinks};
+use crate::dir_size::{recursive_size, recursive_sizes};
 use crate::file_info::FileInfo;
+use crate::filter;
+use crate::formatting::format_size;
+use crate::icons::icon_prefix;
+use crate::sort::sort_entries;
 
 /// Tree drawing characters for different positions
-const TREE_BRANCH: &str = "├── ";
-const TREE_LAST: &str = "└── ";
-const TREE_VERTICAL: &str = "│   ";
-const TREE_SPACE: &str = "    ";
+pub(super) const TREE_BRANCH: &str = "├── ";
+pub(super) const TREE_LAST: &str = "└── ";
+pub(super) const TREE_VERTICAL: &str = "│   ";
+pub(super) const TREE_SPACE: &str = "    ";
 
-/// Maximum depth to prevent infinite recursion
-const MAX_DEPTH: usize = 20;
-
-/// Reads directory entries, filters hidden files, and sorts alphabetically.
+/// Reads directory entries, applies the entry filters, and sorts them as configured.
 ///
 /// # Arguments
 ///
@@ -31,26 +35,81 @@ const MAX_DEPTH: usize = 20;
 /// # Returns
 ///
 /// A vector of sorted directory entries, or empty vector on error
-fn read_and_sort_entries(path: &Path, config: &Config) -> Vec<DirEntry> {
+pub(super) fn read_and_sort_entries(path: &Path, config: &Config) -> Vec<DirEntry> {
     fs::read_dir(path)
         .map(|entries| {
             let mut valid_entries: Vec<_> = entries
                 .filter_map(|e| e.ok())
+                .filter(|entry| filter::is_visible(&entry.file_name().to_string_lossy(), config))
+                .filter(|entry| {
+                    let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+                    !filter::is_git_ignored(&entry.path(), is_dir, config)
+                })
+                // Directories are kept regardless of metadata filters to preserve the structure
                 .filter(|entry| {
-                    config.show_hidden || !entry.file_name().to_string_lossy().starts_with('.')
+                    entry.file_type().is_ok_and(|file_type| file_type.is_dir())
+                        || entry.metadata().is_ok_and(|metadata| {
+                            filter::matches_metadata(&entry.file_name().to_string_lossy(), &metadata, config)
+                                && filter::matches_contents(&entry.path(), &metadata, config)
+                        })
                 })
                 .collect();
 
-            // Sort entries alphabetically
-            valid_entries.sort_by(|a, b| {
-                let a_name = a.file_name();
-                let b_name = b.file_name();
-                a_name.cmp(&b_name)
-            });
+            sort_entries(&mut valid_entries, config);
 
             valid_entries
         })
-        .unwrap_or_else(|_| Vec::new())
+        .unwrap_or_else(|e| {
+            warn!("cannot read directory {}: {}", path.display(), e);
+            Vec::new()
+        })
+}
+
+/// Orders a directory's entries for `--collapse-files N`: subdirectories
+/// first, then at most N files.
+///
+/// Without the option, all entries are kept in their sorted order.
+///
+/// # Arguments
+///
+/// * `entries` - The sorted entries of one directory
+/// * `is_dir` - Whether an entry is a directory (symlinks to directories are files)
+/// * `config` - Configuration holding the file limit
+///
+/// # Returns
+///
+/// The entries to show, and the number of files left out
+pub(super) fn collapse_files<T>(
+    entries: impl IntoIterator<Item = T>,
+    is_dir: impl Fn(&T) -> bool,
+    config: &Config,
+) -> (Vec<T>, usize) {
+    let Some(limit) = config.collapse_files else {
+        return (entries.into_iter().collect(), 0);
+    };
+    let (mut shown, files): (Vec<T>, Vec<T>) = entries.into_iter().partition(|entry| is_dir(entry));
+    let collapsed = files.len().saturating_sub(limit);
+    shown.extend(files.into_iter().take(limit));
+    (shown, collapsed)
+}
+
+/// Returns the `[size]  ` label written before an entry's name with
+/// `--recursive-sizes`, as `tree --du -h` does: the recursive size of a
+/// directory, and the size of anything else.
+fn size_label(entry: &DirEntry) -> String {
+    let path = entry.path();
+    let size = match path.symlink_metadata() {
+        Ok(metadata) if metadata.is_dir() => recursive_size(&path),
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    };
+    format!("[{:>5}]  ", format_size(size))
+}
+
+/// Returns the `(+K files)` line that ends a directory with collapsed files.
+pub(super) fn collapsed_files_label(collapsed: usize) -> String {
+    let noun = if collapsed == 1 { "file" } else { "files" };
+    format!("(+{} {})", collapsed, noun).bright_black().to_string()
 }
 
 /// Displays directory contents in a tree-like structure.
@@ -62,90 +121,217 @@ fn read_and_sort_entries(path: &Path, config: &Config) -> Vec<DirEntry> {
 ///
 /// * `entries` - Vector of directory entries to display
 /// * `config` - Configuration specifying display options
-pub fn display(_entries: &[IoResult<DirEntry>], config: &Config) {
+/// * `out` - Destination for the tree
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn display(_entries: &[IoResult<DirEntry>], config: &Config, out: &mut dyn Write) -> IoResult<()> {
     let path = Path::new(&config.path);
 
     // Display the root directory name
-    println!("{}", path.display().to_string().bright_blue().bold());
+    writeln!(out, "{}", path.display().to_string().bright_blue().bold())?;
 
     // Start tree traversal from the root
     let valid_entries = read_and_sort_entries(path, config);
-    if !valid_entries.is_empty() {
-        display_tree_recursive(&valid_entries, "", true, config, 0);
+    let mut counts = TreeCounts::default();
+    if config.recursive_sizes {
+        // Measure the top-level directories in parallel; deeper levels are then cached
+        let directories: Vec<PathBuf> = valid_entries
+            .iter()
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+            .map(DirEntry::path)
+            .collect();
+        recursive_sizes(&directories);
+    }
+    display_tree(valid_entries, config, &mut counts, out)?;
+    writeln!(out, "\n{}", counts.describe(config))
+}
+
+/// Numbers of directories and files in a tree, as the `tree` command reports them.
+///
+/// Symlinks to directories count as files, since they are not descended into.
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct TreeCounts {
+    pub(super) directories: usize,
+    pub(super) files: usize,
+}
+
+impl TreeCounts {
+    /// Counts the directories and files among a directory's entries.
+    fn of(entries: &[DirEntry]) -> Self {
+        let directories = entries
+            .iter()
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+            .count();
+        Self {
+            directories,
+            files: entries.len() - directories,
+        }
+    }
+
+    /// Adds the counts of another directory.
+    pub(super) fn add(&mut self, other: Self) {
+        self.directories += other.directories;
+        self.files += other.files;
     }
+
+    /// Describes the counts, e.g. "14 directories, 132 files", or only the
+    /// directories with `-d`, as `tree -d` does.
+    pub(super) fn describe(self, config: &Config) -> String {
+        let plural = |count: usize, one: &str, many: &str| format!("{} {}", count, if count == 1 { one } else { many });
+        let directories = plural(self.directories, "directory", "directories");
+        if config.dirs_only {
+            return directories;
+        }
+        format!("{}, {}", directories, plural(self.files, "file", "files"))
+    }
+
+    /// Returns the ` (2 directories, 5 files)` label written after a
+    /// directory's name with `--dir-counts`.
+    pub(super) fn label(self, config: &Config) -> String {
+        format!(" {}", format!("({})", self.describe(config)).bright_black())
+    }
+}
+
+/// One directory of the tree being written by [`display_tree`].
+struct Level {
+    /// Entries still to be written, in order
+    entries: std::vec::IntoIter<DirEntry>,
+    /// Files left out by `--collapse-files`, written as the level's last line
+    collapsed: usize,
+    /// Indentation of the level's lines
+    prefix: String,
 }
 
-/// Recursively displays directory contents in tree format.
+impl Level {
+    /// Starts a level with a directory's entries, adding them to the counts.
+    fn new(entries: Vec<DirEntry>, prefix: String, config: &Config, counts: &mut TreeCounts) -> Self {
+        // Collapsed files are counted too, as the (+K files) line stands for them
+        counts.add(TreeCounts::of(&entries));
+        let is_dir = |entry: &DirEntry| entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+        let (entries, collapsed) = collapse_files(entries, is_dir, config);
+        Self {
+            entries: entries.into_iter(),
+            collapsed,
+            prefix,
+        }
+    }
+
+    /// Checks whether no line follows the next entry at this level.
+    fn next_is_last(&self) -> bool {
+        self.entries.len() == 1 && self.collapsed == 0
+    }
+}
+
+/// Displays directory contents in tree format.
+///
+/// The tree is walked depth first with an explicit stack of open directories
+/// instead of recursion, and each entry is written as soon as its directory
+/// has been read, so large trees start appearing at once and deep ones cannot
+/// exhaust the stack. Only directories within the depth limit are read.
 ///
 /// # Arguments
 ///
-/// * `entries` - Vector of directory entries to display
-/// * `prefix` - Current indentation prefix for tree structure
-/// * `is_root` - Whether this is the root level
+/// * `entries` - The sorted entries of the root directory
 /// * `config` - Configuration specifying display options
-/// * `depth` - Current recursion depth
-fn display_tree_recursive(
-    entries: &[DirEntry],
-    prefix: &str,
-    _is_root: bool,
-    config: &Config,
-    depth: usize,
-) {
-    // Check user-specified depth limit first, then absolute maximum
-    let max_allowed_depth = config.tree_depth.unwrap_or(MAX_DEPTH);
-    if depth >= max_allowed_depth || depth > MAX_DEPTH {
-        return;
+/// * `counts` - Directories and files shown so far, for the summary line
+/// * `out` - Destination for the tree lines
+fn display_tree(entries: Vec<DirEntry>, config: &Config, counts: &mut TreeCounts, out: &mut dyn Write) -> IoResult<()> {
+    if config.max_depth() == 0 {
+        return Ok(());
     }
+    let mut levels = vec![Level::new(entries, String::new(), config, counts)];
 
-    let total_entries = entries.len();
-
-    for (index, entry) in entries.iter().enumerate() {
-        let is_last = index == total_entries - 1;
+    while let Some(level) = levels.last_mut() {
+        let is_last = level.next_is_last();
+        let Some(entry) = level.entries.next() else {
+            if level.collapsed > 0 {
+                writeln!(out, "{}{}{}", level.prefix, TREE_LAST, collapsed_files_label(level.collapsed))?;
+            }
+            levels.pop();
+            continue;
+        };
+        let prefix = level.prefix.clone();
+        let depth = levels.len() - 1;
         let file_name = entry.file_name();
         let file_name_str = file_name.to_string_lossy();
 
-        // Skip hidden files unless explicitly requested
-        if !config.show_hidden && file_name_str.starts_with('.') {
-            continue;
-        }
-
         // Determine tree symbols
         let tree_symbol = if is_last { TREE_LAST } else { TREE_BRANCH };
         let next_prefix = if is_last { TREE_SPACE } else { TREE_VERTICAL };
 
         // Get file info for coloring
-        if let Ok(file_info) = FileInfo::from_path(entry.path()) {
-            let display_name = format_file_name(&file_name_str, &file_info, config);
-            println!("{}{}{}", prefix, tree_symbol, display_name);
-
-            // Recursively display subdirectories
-            if file_info.is_directory() {
-                let sub_entries = read_and_sort_entries(&entry.path(), config);
-                if !sub_entries.is_empty() {
-                    let new_prefix = format!("{}{}", prefix, next_prefix);
-                    display_tree_recursive(&sub_entries, &new_prefix, false, config, depth + 1);
-                }
-            }
-        } else {
+        let Ok(file_info) = FileInfo::from_path(entry.path(), config) else {
             // Handle cases where file info can't be retrieved
-            let display_name = format_file_name(&file_name_str, &FileInfo::default(), config);
-            println!("{}{}{}", prefix, tree_symbol, display_name);
+            let display_name = format_file_name(&file_name_str, &entry.path(), &FileInfo::default(), config);
+            writeln!(out, "{}{}{}", prefix, tree_symbol, display_name)?;
+            continue;
+        };
+        let display_name = format_file_name(&file_name_str, &entry.path(), &file_info, config);
+        let size = if config.recursive_sizes { size_label(&entry) } else { String::new() };
+
+        // Descend into subdirectories within the depth limit, without following symlinks found during traversal
+        let is_symlink = entry.file_type().is_ok_and(|file_type| file_type.is_symlink());
+        let descend = file_info.is_directory() && !is_symlink && depth + 1 < config.max_depth();
+        let sub_entries = if descend {
+            read_and_sort_entries(&entry.path(), config)
+        } else {
+            Vec::new()
+        };
+        // Only directories whose contents are shown get counts
+        let dir_counts = if config.dir_counts && descend {
+            TreeCounts::of(&sub_entries).label(config)
+        } else {
+            String::new()
+        };
+        writeln!(out, "{}{}{}{}{}", prefix, tree_symbol, size, display_name, dir_counts)?;
+        if !sub_entries.is_empty() {
+            let sub_prefix = format!("{}{}", prefix, next_prefix);
+            levels.push(Level::new(sub_entries, sub_prefix, config, counts));
         }
     }
+    Ok(())
 }
 
 /// Formats a file name with appropriate colors and interactive features.
 ///
+/// In interactive mode, directories link to `fls://` URLs with
+/// `--dir-links fls`, so a configured terminal re-lists them when clicked.
+/// Files with an opener in the configuration file link to `fls://` URLs
+/// too, so clicking them runs the configured command. Symlinks are followed
+/// by ` -> target`, with broken targets in red, and by the badges of failed
+/// `--check-case-collisions` and `--check-names` checks.
+///
 /// # Arguments
 ///
 /// * `name` - The file name to format
+/// * `path` - The full path to the entry, for hyperlinks
 /// * `file_info` - File information for determining colors
 /// * `config` - Configuration for interactive mode
 ///
 /// # Returns
 ///
 /// A formatted string with colors and optional hyperlinks
-fn format_file_name(name: &str, file_info: &FileInfo, config: &Config) -> String {
-    format_with_color(name, file_info, config.interactive)
+pub(super) fn format_file_name(name: &str, path: &Path, file_info: &FileInfo, config: &Config) -> String {
+    let colored_name = format_with_color(name, file_info);
+    let icon = icon_prefix(name, &file_info.file_type, file_info.broken_link, config);
+    let suffix = file_info
+        .link_target
+        .as_ref()
+        .map(|target| link_suffix(target, file_info.broken_link))
+        .unwrap_or_default();
+    let name = if !config.interactive {
+        colored_name
+    } else if config.dir_links == DirLinks::Fls
+        && (file_info.is_directory() || config.openers.command_for(path).is_some())
+    {
+        make_listing_link(path, &colored_name)
+    } else {
+        make_clickable_link(name, path, &colored_name)
+    };
+    let badges = check_badges(path, config);
+    let badges = if badges.is_empty() { badges } else { badges.red().bold().to_string() };
+    format!("{}{}{}{}", icon, name, suffix, badges)
 }
 
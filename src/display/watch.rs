@@ -0,0 +1,252 @@
+//! Live-updating tree display.
+//!
+//! This module implements `--tree --watch`: the tree is read once into an
+//! in-memory model, then filesystem events (creates, removes, renames, and
+//! metadata changes) are applied to that model incrementally and the view is
+//! redrawn, without walking the whole directory again.
+
+use colored::*;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use super::tree::{collapse_files, collapsed_files_label, format_file_name, read_and_sort_entries, TreeCounts, TREE_BRANCH, TREE_LAST, TREE_SPACE, TREE_VERTICAL};
+use crate::config::Config;
+use crate::file_info::FileInfo;
+use crate::filter;
+use crate::sort::path_key;
+
+/// How long to wait for more events before redrawing, so bursts cause one redraw
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// A file or directory in the watched tree.
+struct Node {
+    name: String,
+    path: PathBuf,
+    file_info: FileInfo,
+    /// Whether the node is a real directory (symlinked directories are not descended)
+    is_dir: bool,
+    children: Vec<Node>,
+}
+
+impl Node {
+    /// Reads a node and, for directories within the depth limit, its subtree.
+    fn load(path: &Path, depth: usize, config: &Config) -> Self {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let file_info = FileInfo::from_path(path, config).unwrap_or_default();
+        let is_dir = fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_dir());
+
+        let children = if is_dir && depth < config.max_depth() {
+            read_and_sort_entries(path, config)
+                .iter()
+                .map(|entry| Node::load(&entry.path(), depth + 1, config))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            name,
+            path: path.to_path_buf(),
+            file_info,
+            is_dir,
+            children,
+        }
+    }
+}
+
+/// Displays the tree and keeps it updated as the filesystem changes.
+///
+/// Runs until interrupted (Ctrl-C).
+///
+/// # Arguments
+///
+/// * `config` - Configuration specifying display options
+///
+/// # Errors
+///
+/// Prints an error message to stderr if the filesystem watcher cannot be started.
+pub fn display(config: &Config) {
+    let root_path = Path::new(&config.path);
+    let mut root = Node::load(root_path, 0, config);
+    // Event paths are absolute, so match them against the canonical root
+    let canonical_root = fs::canonicalize(root_path).unwrap_or_else(|_| root_path.to_path_buf());
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = match notify::recommended_watcher(sender) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("{}: {}", "Error".red().bold(), e);
+            return;
+        }
+    };
+    if let Err(e) = watcher.watch(root_path, RecursiveMode::Recursive) {
+        eprintln!("{}: {}", "Error".red().bold(), e);
+        return;
+    }
+
+    redraw(&root, config);
+
+    while let Ok(event) = receiver.recv() {
+        apply_event(&mut root, &canonical_root, event, config);
+
+        // Coalesce bursts of events (e.g. a build writing many files) into one redraw
+        while let Ok(event) = receiver.recv_timeout(DEBOUNCE) {
+            apply_event(&mut root, &canonical_root, event, config);
+        }
+
+        redraw(&root, config);
+    }
+}
+
+/// Applies one watcher event to the model.
+///
+/// Each affected path is re-checked on disk: existing paths are inserted or
+/// refreshed, missing paths are removed. This handles creates, removes, both
+/// halves of renames, and metadata changes uniformly.
+fn apply_event(root: &mut Node, canonical_root: &Path, event: notify::Result<Event>, config: &Config) {
+    let event = match event {
+        Ok(event) => event,
+        Err(e) => {
+            warn!("watch error: {}", e);
+            return;
+        }
+    };
+
+    // Reads (including our own when refreshing nodes) never change the tree
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+
+    for path in &event.paths {
+        let Ok(relative) = path.strip_prefix(canonical_root) else {
+            continue;
+        };
+        let components: Vec<String> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect();
+        if components.is_empty() || components.len() > config.max_depth() {
+            continue;
+        }
+
+        debug!("{:?} {}", event.kind, relative.display());
+        let model_path = root.path.join(relative);
+        update_path(root, &components, &model_path, 0, config);
+    }
+}
+
+/// Inserts, refreshes, or removes the node at `components` below `node`.
+///
+/// `depth` is the depth of `node` itself (0 for the root).
+fn update_path(node: &mut Node, components: &[String], model_path: &Path, depth: usize, config: &Config) {
+    let Some((name, rest)) = components.split_first() else {
+        return;
+    };
+
+    if !rest.is_empty() {
+        // Descend; parents that are not in the model (filtered or too deep) are ignored
+        if let Some(child) = node.children.iter_mut().find(|child| &child.name == name) {
+            update_path(child, rest, model_path, depth + 1, config);
+        }
+        return;
+    }
+
+    let position = node.children.iter().position(|child| &child.name == name);
+    let listed = fs::symlink_metadata(model_path).is_ok_and(|metadata| {
+        filter::is_visible(name, config)
+            && !filter::is_git_ignored(model_path, metadata.is_dir(), config)
+            && (metadata.is_dir()
+                || (filter::matches_metadata(name, &metadata, config)
+                    && filter::matches_contents(model_path, &metadata, config)))
+    });
+
+    match (position, listed) {
+        (Some(index), false) => {
+            node.children.remove(index);
+        }
+        (Some(index), true) => {
+            // Refresh the type/permissions (e.g. a file became executable), keeping the subtree
+            node.children[index].file_info = FileInfo::from_path(model_path, config).unwrap_or_default();
+        }
+        (None, true) => {
+            let child = Node::load(model_path, depth + 1, config);
+            let key = path_key(model_path, config);
+            let index = node
+                .children
+                .partition_point(|existing| path_key(&existing.path, config) < key);
+            node.children.insert(index, child);
+        }
+        (None, false) => {}
+    }
+}
+
+/// Clears the screen and draws the current model.
+fn redraw(root: &Node, config: &Config) {
+    let mut lines = Vec::new();
+    render(&root.children, "", 1, config, &mut lines);
+    let mut counts = TreeCounts::default();
+    count(&root.children, &mut counts);
+
+    let mut stdout = io::stdout().lock();
+    let _ = write!(stdout, "\x1b[2J\x1b[H");
+    let _ = writeln!(stdout, "{}", config.path.bright_blue().bold());
+    for line in lines {
+        let _ = writeln!(stdout, "{}", line);
+    }
+    let _ = writeln!(stdout, "\n{}", counts.describe(config));
+    let _ = writeln!(stdout, "\n{}", "Watching for changes (Ctrl-C to exit)".bright_black());
+    let _ = stdout.flush();
+}
+
+/// Adds up the directories and files of a model's nodes, as the static tree view does.
+fn count(nodes: &[Node], counts: &mut TreeCounts) {
+    let directories = nodes.iter().filter(|node| node.is_dir).count();
+    counts.add(TreeCounts {
+        directories,
+        files: nodes.len() - directories,
+    });
+    for node in nodes {
+        count(&node.children, counts);
+    }
+}
+
+/// Renders nodes into tree lines using the same symbols as the static tree
+/// view; `depth` is the level of `nodes`, starting at 1.
+fn render(nodes: &[Node], prefix: &str, depth: usize, config: &Config, lines: &mut Vec<String>) {
+    let (nodes, collapsed) = collapse_files(nodes, |node: &&Node| node.is_dir, config);
+    let total_nodes = nodes.len() + usize::from(collapsed > 0);
+    for (index, node) in nodes.into_iter().enumerate() {
+        let is_last = index == total_nodes - 1;
+        let tree_symbol = if is_last { TREE_LAST } else { TREE_BRANCH };
+        let next_prefix = if is_last { TREE_SPACE } else { TREE_VERTICAL };
+
+        let display_name = format_file_name(&node.name, &node.path, &node.file_info, config);
+        // Children are only loaded within the depth limit
+        let dir_counts = if config.dir_counts && node.is_dir && depth < config.max_depth() {
+            let directories = node.children.iter().filter(|child| child.is_dir).count();
+            TreeCounts {
+                directories,
+                files: node.children.len() - directories,
+            }
+            .label(config)
+        } else {
+            String::new()
+        };
+        lines.push(format!("{}{}{}{}", prefix, tree_symbol, display_name, dir_counts));
+
+        if node.is_dir {
+            render(&node.children, &format!("{}{}", prefix, next_prefix), depth + 1, config, lines);
+        }
+    }
+    if collapsed > 0 {
+        lines.push(format!("{}{}{}", prefix, TREE_LAST, collapsed_files_label(collapsed)));
+    }
+}
@@ -0,0 +1,176 @@
+//! Table columns filled by user commands for `--exec-column`.
+//!
+//! Each column runs a shell command once per listed entry and shows the first
+//! line of its output, e.g. `--exec-column Kind='file -b {}'`. `{}` is
+//! replaced with the entry's quoted path, as for the openers of `fls open`.
+//! Commands for different entries run in parallel (one per CPU at a time),
+//! and a command that does not finish within the timeout is killed.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tabled::Tabled;
+use tracing::{debug, warn};
+
+use crate::file_info::FileInfo;
+use crate::openers::command_line;
+use crate::parallel::map_ordered;
+use crate::spinner::Spinner;
+
+/// How often a running command is checked for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Cell shown when a command fails to start or exits unsuccessfully
+const FAILED_CELL: &str = "?";
+
+/// Cell shown when a command is killed for exceeding the timeout
+const TIMEOUT_CELL: &str = "timeout";
+
+/// A table column computed by a shell command.
+#[derive(Clone, Debug)]
+pub struct ExecColumn {
+    /// Column header
+    pub name: String,
+    /// Shell command run for each entry, optionally containing `{}`
+    pub command: String,
+}
+
+/// Parses a `NAME=COMMAND` pair for the `--exec-column` option.
+///
+/// # Arguments
+///
+/// * `value` - The option value, e.g. `Kind=file -b {}`
+///
+/// # Returns
+///
+/// The column, or an error if the value has no `=`, an empty name or command,
+/// or a name that is already a built-in column
+pub fn parse_exec_column(value: &str) -> Result<ExecColumn, String> {
+    let (name, command) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=COMMAND, got '{}'", value))?;
+    let (name, command) = (name.trim(), command.trim());
+    if name.is_empty() || command.is_empty() {
+        return Err(format!("expected NAME=COMMAND, got '{}'", value));
+    }
+    if FileInfo::headers().iter().any(|header| header.eq_ignore_ascii_case(name)) {
+        return Err(format!("'{}' is already a column; choose another name", name));
+    }
+    Ok(ExecColumn {
+        name: name.to_string(),
+        command: command.to_string(),
+    })
+}
+
+/// Runs every column command for every path in parallel.
+///
+/// # Arguments
+///
+/// * `paths` - The entries to run the commands for
+/// * `columns` - The columns to compute
+/// * `timeout` - How long each command may run
+///
+/// # Returns
+///
+/// For each path, in order, the cells of each column in order
+pub fn compute(paths: &[PathBuf], columns: &[ExecColumn], timeout: Duration) -> Vec<Vec<String>> {
+    if paths.is_empty() || columns.is_empty() {
+        return vec![Vec::new(); paths.len()];
+    }
+
+    let _spinner = Spinner::start("Running column commands...");
+
+    map_ordered(paths, |path| {
+        columns.iter().map(|column| run(&column.command, path, timeout)).collect()
+    })
+}
+
+/// Runs a column command for one entry and returns its cell.
+///
+/// Only the first line of the output is kept, trimmed, with control
+/// characters replaced by spaces so it cannot break the table or inject
+/// terminal escape sequences.
+///
+/// # Arguments
+///
+/// * `command` - The shell command, optionally containing `{}`
+/// * `path` - Path to the entry
+/// * `timeout` - How long the command may run
+///
+/// # Returns
+///
+/// The cell text, "?" if the command failed, or "timeout" if it was killed
+pub fn run(command: &str, path: &Path, timeout: Duration) -> String {
+    let command_line = command_line(command, path);
+    let mut child = match Command::new("sh")
+        .arg("-c")
+        .arg(&command_line)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            warn!("cannot run '{}': {}", command_line, e);
+            return FAILED_CELL.to_string();
+        }
+    };
+
+    // Read on another thread, so a chatty command cannot block on a full pipe
+    let reader = child.stdout.take().map(|mut stdout| {
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            let _ = stdout.read_to_end(&mut output);
+            output
+        })
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() < deadline => thread::sleep(POLL_INTERVAL),
+            Ok(None) => {
+                debug!("killing '{}' after {:?}", command_line, timeout);
+                let _ = child.kill();
+                let _ = child.wait();
+                return TIMEOUT_CELL.to_string();
+            }
+            Err(e) => {
+                warn!("cannot wait for '{}': {}", command_line, e);
+                break None;
+            }
+        }
+    };
+
+    // Background processes started by the command may keep the pipe open, so
+    // the output is only waited for until the deadline
+    while reader.as_ref().is_some_and(|reader| !reader.is_finished()) && Instant::now() < deadline {
+        thread::sleep(POLL_INTERVAL);
+    }
+    let output = match reader {
+        Some(reader) if reader.is_finished() => reader.join().unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    match status {
+        Some(status) if status.success() => {
+            let output = String::from_utf8_lossy(&output);
+            output
+                .lines()
+                .next()
+                .unwrap_or("")
+                .trim()
+                .chars()
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .collect()
+        }
+        Some(status) => {
+            debug!("'{}' exited with {}", command_line, status);
+            FAILED_CELL.to_string()
+        }
+        None => FAILED_CELL.to_string(),
+    }
+}
@@ -0,0 +1,222 @@
+//! Conversion of colored terminal output to other formats.
+//!
+//! Listings are rendered exactly as they would be for a terminal, and the ANSI
+//! escape sequences are translated afterwards, so an export always matches
+//! what `fls` shows on screen.
+
+/// Standard and bright colors for SGR codes 30–37 and 90–97 (and their backgrounds)
+const PALETTE: [&str; 16] = [
+    "#000000", "#cd3131", "#0dbc79", "#e5e510", "#2472c8", "#bc3fbc", "#11a8cd", "#e5e5e5",
+    "#666666", "#f14c4c", "#23d18b", "#f5f543", "#3b8eea", "#d670d6", "#29b8db", "#ffffff",
+];
+
+/// Colors of the surrounding block, resembling a dark terminal
+const BLOCK_STYLE: &str = "background:#1e1e1e;color:#d4d4d4;padding:0.5em";
+
+/// Text attributes accumulated from SGR (Select Graphic Rendition) sequences.
+#[derive(Clone, Default, PartialEq)]
+struct TextStyle {
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    foreground: Option<String>,
+    background: Option<String>,
+}
+
+impl TextStyle {
+    /// Updates the style from the numeric parameters of an SGR sequence.
+    fn apply(&mut self, params: &[u16]) {
+        let mut params = params.iter().copied();
+        while let Some(code) = params.next() {
+            match code {
+                0 => *self = TextStyle::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                30..=37 => self.foreground = Some(PALETTE[usize::from(code - 30)].to_string()),
+                90..=97 => self.foreground = Some(PALETTE[usize::from(code - 90 + 8)].to_string()),
+                39 => self.foreground = None,
+                40..=47 => self.background = Some(PALETTE[usize::from(code - 40)].to_string()),
+                100..=107 => self.background = Some(PALETTE[usize::from(code - 100 + 8)].to_string()),
+                49 => self.background = None,
+                38 => self.foreground = extended_color(&mut params),
+                48 => self.background = extended_color(&mut params),
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the inline CSS for this style, or `None` for unstyled text.
+    fn css(&self) -> Option<String> {
+        let mut declarations = Vec::new();
+        if let Some(color) = &self.foreground {
+            declarations.push(format!("color:{}", color));
+        }
+        if let Some(color) = &self.background {
+            declarations.push(format!("background-color:{}", color));
+        }
+        if self.bold {
+            declarations.push("font-weight:bold".to_string());
+        }
+        if self.dim {
+            declarations.push("opacity:0.7".to_string());
+        }
+        if self.italic {
+            declarations.push("font-style:italic".to_string());
+        }
+        if self.underline {
+            declarations.push("text-decoration:underline".to_string());
+        }
+
+        (!declarations.is_empty()).then(|| declarations.join(";"))
+    }
+}
+
+/// Reads a 256-color (`5;N`) or true-color (`2;R;G;B`) value following SGR 38/48.
+fn extended_color(params: &mut impl Iterator<Item = u16>) -> Option<String> {
+    match params.next()? {
+        5 => params.next().map(palette_256),
+        2 => {
+            let (red, green, blue) = (params.next()?, params.next()?, params.next()?);
+            Some(format!("#{:02x}{:02x}{:02x}", red.min(255), green.min(255), blue.min(255)))
+        }
+        _ => None,
+    }
+}
+
+/// Maps an xterm 256-color index to a CSS color.
+fn palette_256(index: u16) -> String {
+    match index {
+        0..=15 => PALETTE[usize::from(index)].to_string(),
+        16..=231 => {
+            let index = index - 16;
+            let level = |value: u16| if value == 0 { 0 } else { value * 40 + 55 };
+            format!("#{:02x}{:02x}{:02x}", level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        _ => {
+            let gray = (index.min(255) - 232) * 10 + 8;
+            format!("#{:02x}{:02x}{:02x}", gray, gray, gray)
+        }
+    }
+}
+
+/// Escapes text for use in HTML or SVG content and attribute values.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Converts text containing ANSI colors and OSC 8 hyperlinks into HTML.
+///
+/// Colors and text attributes become inline-styled `<span>` elements and
+/// hyperlinks (from interactive mode) become `<a>` elements. The result is a
+/// self-contained `<pre>` block that can be pasted into a web page.
+///
+/// # Arguments
+///
+/// * `input` - Terminal output including escape sequences
+///
+/// # Returns
+///
+/// An HTML fragment reproducing the colored output
+pub fn ansi_to_html(input: &str) -> String {
+    let mut html = format!("<pre class=\"fls\" style=\"{}\">", BLOCK_STYLE);
+    let mut style = TextStyle::default();
+    let mut span_open = false;
+    let mut link_open = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            if !span_open {
+                if let Some(css) = style.css() {
+                    html.push_str(&format!("<span style=\"{}\">", css));
+                    span_open = true;
+                }
+            }
+            html.push_str(&escape_html(&c.to_string()));
+            continue;
+        }
+
+        match chars.next() {
+            // CSI sequence: parameters up to a final byte; only SGR ('m') affects the output
+            Some('[') => {
+                let mut sequence = String::new();
+                let mut final_byte = None;
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        final_byte = Some(c);
+                        break;
+                    }
+                    sequence.push(c);
+                }
+                if final_byte == Some('m') {
+                    let params: Vec<u16> = sequence.split(';').map(|param| param.parse().unwrap_or(0)).collect();
+                    let mut new_style = style.clone();
+                    new_style.apply(&params);
+                    if new_style != style && span_open {
+                        html.push_str("</span>");
+                        span_open = false;
+                    }
+                    style = new_style;
+                }
+            }
+            // OSC sequence terminated by BEL or ESC \; only hyperlinks (OSC 8) are kept
+            Some(']') => {
+                let mut sequence = String::new();
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                    sequence.push(c);
+                }
+                if let Some(link) = sequence.strip_prefix("8;") {
+                    let url = link.split_once(';').map(|(_, url)| url).unwrap_or_default();
+                    if span_open {
+                        html.push_str("</span>");
+                        span_open = false;
+                    }
+                    if link_open {
+                        html.push_str("</a>");
+                        link_open = false;
+                    }
+                    if !url.is_empty() {
+                        html.push_str(&format!("<a href=\"{}\">", escape_html(url)));
+                        link_open = true;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if span_open {
+        html.push_str("</span>");
+    }
+    if link_open {
+        html.push_str("</a>");
+    }
+    html.push_str("</pre>\n");
+    html
+}
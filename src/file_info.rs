@@ -6,11 +6,13 @@
 
 use std::fs;
 use std::path::Path;
-use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tabled::Tabled;
 use users::{get_group_by_gid, get_user_by_uid};
 
-use crate::formatting::{format_octal_permissions, format_size, format_time};
+use crate::config::Config;
+use crate::formatting::{format_age, format_bytes, format_iso8601, format_octal_permissions, format_short_time, format_size, format_utc_time};
 
 /// Represents file information for table display.
 ///
@@ -21,8 +23,14 @@ use crate::formatting::{format_octal_permissions, format_size, format_time};
 pub struct FileInfo {
     #[tabled(rename = "Name")]
     pub name: String,
+    #[tabled(rename = "New")]
+    pub new: String,
+    #[tabled(rename = "Inode")]
+    pub inode: String,
     #[tabled(rename = "Type")]
     pub file_type: String,
+    #[tabled(rename = "Ext")]
+    pub ext: String,
     #[tabled(rename = "User Permission")]
     pub user_perms: String,
     #[tabled(rename = "Group Permission")]
@@ -31,46 +39,54 @@ pub struct FileInfo {
     pub other_perms: String,
     #[tabled(rename = "Octal")]
     pub octal: String,
-    #[tabled(rename = "User/Group (Owner)")]
-    pub owner: String,
+    #[tabled(rename = "User")]
+    pub user: String,
+    #[tabled(rename = "Group")]
+    pub group: String,
     #[tabled(rename = "Size")]
     pub size: String,
+    #[tabled(rename = "Unit")]
+    pub size_unit: String,
+    #[tabled(rename = "Δ Size")]
+    pub size_delta: String,
+    #[tabled(rename = "Allocated")]
+    pub allocated: String,
     #[tabled(rename = "Modified")]
     pub modified: String,
+    #[tabled(rename = "Accessed")]
+    pub accessed: String,
+    #[tabled(rename = "Changed")]
+    pub changed: String,
+    #[tabled(rename = "Created")]
+    pub created: String,
+    #[tabled(rename = "Age")]
+    pub age: String,
+    #[tabled(rename = "Heat")]
+    pub heat: String,
     #[tabled(rename = "Items")]
     pub item_count: String,
+    #[tabled(rename = "Inodes")]
+    pub inodes: String,
+    #[tabled(rename = "Hash")]
+    pub hash: String,
+    #[tabled(rename = "Change")]
+    pub change: String,
+    #[tabled(rename = "Comment")]
+    pub comment: String,
+    #[tabled(rename = "Preview")]
+    pub preview: String,
+    /// Cells of the `--exec-column` columns, which follow the built-in ones
+    #[tabled(skip)]
+    pub exec: Vec<String>,
+    /// Where a symlink points, as stored in the link, or None for other entries
+    #[tabled(skip)]
+    pub link_target: Option<String>,
+    /// Whether the entry is a symlink whose target does not exist
+    #[tabled(skip)]
+    pub broken_link: bool,
 }
 
 impl FileInfo {
-    /// Creates a new FileInfo instance from file metadata.
-    ///
-    /// # Arguments
-    ///
-    /// * `name` - The name of the file
-    /// * `metadata` - The file's metadata from the filesystem
-    ///
-    /// # Returns
-    ///
-    /// A new FileInfo instance with all fields populated from the metadata.
-    pub fn from_metadata(name: String, metadata: &fs::Metadata) -> Self {
-        Self {
-            name: name.clone(),
-            file_type: get_file_type(metadata),
-            user_perms: get_user_permissions(metadata),
-            group_perms: get_group_permissions(metadata),
-            other_perms: get_other_permissions(metadata),
-            octal: format_octal_permissions(metadata),
-            owner: get_owner_info(metadata),
-            size: format_size(metadata.len()),
-            modified: format_time(metadata),
-            item_count: if metadata.is_dir() {
-                count_directory_items(&name).unwrap_or_else(|_| "?".to_string())
-            } else {
-                "-".to_string()
-            },
-        }
-    }
-
     /// Creates a new FileInfo instance from file metadata with full path support.
     ///
     /// # Arguments
@@ -78,64 +94,88 @@ impl FileInfo {
     /// * `name` - The name of the file
     /// * `metadata` - The file's metadata from the filesystem
     /// * `path` - The full path to the file
+    /// * `config` - Configuration controlling how values are formatted
     ///
     /// # Returns
     ///
     /// A new FileInfo instance with all fields populated from the metadata.
-    pub fn from_metadata_with_path<P: AsRef<Path>>(name: String, metadata: &fs::Metadata, path: P) -> Self {
+    pub fn from_metadata_with_path<P: AsRef<Path>>(
+        name: String,
+        metadata: &fs::Metadata,
+        path: P,
+        config: &Config,
+    ) -> Self {
+        let (link_target, broken_link) = match link_target(path.as_ref(), metadata) {
+            Some((target, broken)) => (Some(target), broken),
+            None => (None, false),
+        };
         Self {
+            new: if config.highlight_new.is_some_and(|within| is_recently_modified(metadata, within)) {
+                NEW_BADGE.to_string()
+            } else {
+                String::new()
+            },
             name,
+            inode: metadata.ino().to_string(),
             file_type: get_file_type(metadata),
+            ext: file_extension(path.as_ref(), metadata),
             user_perms: get_user_permissions(metadata),
             group_perms: get_group_permissions(metadata),
             other_perms: get_other_permissions(metadata),
             octal: format_octal_permissions(metadata),
-            owner: get_owner_info(metadata),
-            size: format_size(metadata.len()),
-            modified: format_time(metadata),
+            user: user_name(metadata.uid()),
+            group: group_name(metadata.gid()),
+            size: format_entry_size(metadata.len(), config),
+            size_unit: String::new(),
+            size_delta: String::new(),
+            allocated: format_entry_size(allocated_size(metadata), config),
+            modified: format_listing_time(metadata.modified().ok(), config),
+            accessed: format_listing_time(metadata.accessed().ok(), config),
+            changed: format_listing_time(changed_time(metadata), config),
+            created: format_listing_time(metadata.created().ok(), config),
+            age: format_listing_age(metadata.modified().ok(), config),
+            heat: String::new(),
             item_count: if metadata.is_dir() {
                 count_directory_items_by_path(path.as_ref()).unwrap_or_else(|_| "?".to_string())
+            } else if config.format.is_some() {
+                // Left empty, so spreadsheets read the column as numbers
+                String::new()
             } else {
                 "-".to_string()
             },
+            inodes: "1".to_string(),
+            hash: String::new(),
+            change: String::new(),
+            comment: String::new(),
+            preview: String::new(),
+            exec: Vec::new(),
+            link_target,
+            broken_link,
         }
     }
 
     /// Creates a new FileInfo instance from a file path.
     ///
+    /// Symlinks are not followed, so a link is described as a symlink with
+    /// its target, and broken links are described rather than failing.
+    ///
     /// # Arguments
     ///
     /// * `path` - The path to the file
+    /// * `config` - Configuration controlling how values are formatted
     ///
     /// # Returns
     ///
     /// A Result containing the FileInfo instance or an error if the file cannot be accessed.
-    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+    pub fn from_path<P: AsRef<Path>>(path: P, config: &Config) -> Result<Self, std::io::Error> {
         let path = path.as_ref();
-        let metadata = fs::metadata(path)?;
+        let metadata = fs::symlink_metadata(path)?;
         let name = path.file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("")
             .to_string();
-        
-        let item_count = if metadata.is_dir() {
-            count_directory_items_by_path(path).unwrap_or_else(|_| "?".to_string())
-        } else {
-            "-".to_string()
-        };
-        
-        Ok(Self {
-            name,
-            file_type: get_file_type(&metadata),
-            user_perms: get_user_permissions(&metadata),
-            group_perms: get_group_permissions(&metadata),
-            other_perms: get_other_permissions(&metadata),
-            octal: format_octal_permissions(&metadata),
-            owner: get_owner_info(&metadata),
-            size: format_size(metadata.len()),
-            modified: format_time(&metadata),
-            item_count,
-        })
+
+        Ok(Self::from_metadata_with_path(name, &metadata, path, config))
     }
 
     /// Checks if this file is a directory.
@@ -146,43 +186,147 @@ impl FileInfo {
     pub fn is_directory(&self) -> bool {
         self.file_type == "Directory"
     }
-
-    /// Checks if this file is executable.
-    ///
-    /// # Returns
-    ///
-    /// `true` if the file is executable, `false` otherwise.
-    pub fn is_executable(&self) -> bool {
-        self.file_type == "Executable"
-    }
-
-    /// Checks if this file is hidden (starts with a dot).
-    ///
-    /// # Returns
-    ///
-    /// `true` if the file is hidden, `false` otherwise.
-    pub fn is_hidden(&self) -> bool {
-        self.name.starts_with('.')
-    }
 }
 
 impl Default for FileInfo {
     fn default() -> Self {
         Self {
             name: "".to_string(),
+            new: String::new(),
+            inode: String::new(),
             file_type: "File".to_string(),
+            ext: String::new(),
             user_perms: "None".to_string(),
             group_perms: "None".to_string(),
             other_perms: "None".to_string(),
             octal: "000".to_string(),
-            owner: "unknown/unknown".to_string(),
+            user: "unknown".to_string(),
+            group: "unknown".to_string(),
             size: "0B".to_string(),
+            size_unit: String::new(),
+            size_delta: String::new(),
+            allocated: "0B".to_string(),
             modified: "Unknown".to_string(),
+            accessed: "Unknown".to_string(),
+            changed: "Unknown".to_string(),
+            created: "Unknown".to_string(),
+            age: "Unknown".to_string(),
+            heat: String::new(),
             item_count: "-".to_string(),
+            inodes: "1".to_string(),
+            hash: String::new(),
+            change: String::new(),
+            comment: String::new(),
+            preview: String::new(),
+            exec: Vec::new(),
+            link_target: None,
+            broken_link: false,
         }
     }
 }
 
+/// Formats a size for listings, rounded or exact depending on `--bytes`.
+///
+/// # Arguments
+///
+/// * `size` - The size in bytes
+/// * `config` - Configuration specifying whether exact sizes are shown
+///
+/// # Returns
+///
+/// A string like "1.5M", or "1,536,224" with `--bytes`, or "1536224" with `--format`
+pub fn format_entry_size(size: u64, config: &Config) -> String {
+    if config.format.is_some() {
+        return size.to_string();
+    }
+    match (config.exact_bytes, config.deterministic) {
+        // Plain digits, since separators depend on the locale
+        (true, true) => size.to_string(),
+        (true, false) => format_bytes(size, config.locale.numeric),
+        (false, _) => format_size(size),
+    }
+}
+
+/// Formats a timestamp for listings, in the short format or, with
+/// `--deterministic`, as a fixed-width UTC timestamp.
+///
+/// # Arguments
+///
+/// * `time` - The point in time to format, or None if unavailable
+/// * `config` - Configuration specifying the time zone and format
+///
+/// # Returns
+///
+/// A string like "Jun 08 14:30" (month names follow the locale), "2024-06-08T12:30:00Z" with
+/// `--deterministic`, or "2024-06-08T14:30:00+02:00" (empty if unavailable) with `--format`
+pub fn format_listing_time(time: Option<SystemTime>, config: &Config) -> String {
+    if config.deterministic {
+        format_utc_time(time)
+    } else if config.format.is_some() {
+        time.map(|time| format_iso8601(time, config.time_zone)).unwrap_or_default()
+    } else {
+        format_short_time(time, config.time_zone, config.locale.time)
+    }
+}
+
+/// Formats the time since a modification for the Age column.
+///
+/// # Arguments
+///
+/// * `time` - The modification time, or None if unavailable
+/// * `config` - Configuration specifying the format
+///
+/// # Returns
+///
+/// A string like "3d", or the number of seconds (empty if unavailable) with `--format`
+pub fn format_listing_age(time: Option<SystemTime>, config: &Config) -> String {
+    if config.format.is_none() {
+        return format_age(time);
+    }
+    time.map(|time| {
+        SystemTime::now()
+            .duration_since(time)
+            .map_or(0, |age| age.as_secs())
+            .to_string()
+    })
+    .unwrap_or_default()
+}
+
+/// Returns the extension of a file name, without the leading dot.
+///
+/// Directories and names without an extension (including dotfiles such as
+/// `.bashrc`) have none.
+///
+/// # Arguments
+///
+/// * `path` - Path to the entry
+/// * `metadata` - The entry's metadata
+///
+/// # Returns
+///
+/// The extension, or an empty string if there is none
+pub fn file_extension(path: &Path, metadata: &fs::Metadata) -> String {
+    if metadata.is_dir() {
+        return String::new();
+    }
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Returns the disk space allocated to a file, as opposed to its apparent size.
+///
+/// # Arguments
+///
+/// * `metadata` - The file's metadata
+///
+/// # Returns
+///
+/// The allocated size in bytes (the number of 512-byte blocks times 512)
+pub fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    metadata.blocks() * 512
+}
+
 /// Checks if a file is executable by examining its permission bits.
 ///
 /// # Arguments
@@ -196,6 +340,28 @@ pub fn is_executable(metadata: &fs::Metadata) -> bool {
     metadata.permissions().mode() & 0o111 != 0
 }
 
+/// Separator between a symlink's name and its target, as in `ls -l`
+pub const LINK_ARROW: &str = "->";
+
+/// Reads where a symlink points and whether that target exists.
+///
+/// # Arguments
+///
+/// * `path` - Path to the entry
+/// * `metadata` - The entry's own metadata (from `symlink_metadata`)
+///
+/// # Returns
+///
+/// The target as stored in the link and `true` if it is broken, or None if
+/// the entry is not a symlink
+pub fn link_target(path: &Path, metadata: &fs::Metadata) -> Option<(String, bool)> {
+    if !metadata.file_type().is_symlink() {
+        return None;
+    }
+    let target = fs::read_link(path).map_or_else(|_| "?".to_string(), |target| target.display().to_string());
+    Some((target, fs::metadata(path).is_err()))
+}
+
 /// Determines the human-readable file type based on metadata.
 ///
 /// # Arguments
@@ -204,12 +370,22 @@ pub fn is_executable(metadata: &fs::Metadata) -> bool {
 ///
 /// # Returns
 ///
-/// A string describing the file type: "Directory", "Symlink", "Executable", or "File"
+/// A string describing the file type: "Directory", "Symlink", "FIFO", "Socket",
+/// "Block Device", "Character Device", "Executable", or "File"
 pub fn get_file_type(metadata: &fs::Metadata) -> String {
+    let file_type = metadata.file_type();
     if metadata.is_dir() {
         "Directory".to_string()
-    } else if metadata.file_type().is_symlink() {
+    } else if file_type.is_symlink() {
         "Symlink".to_string()
+    } else if file_type.is_fifo() {
+        "FIFO".to_string()
+    } else if file_type.is_socket() {
+        "Socket".to_string()
+    } else if file_type.is_block_device() {
+        "Block Device".to_string()
+    } else if file_type.is_char_device() {
+        "Character Device".to_string()
     } else if is_executable(metadata) {
         "Executable".to_string()
     } else {
@@ -217,6 +393,43 @@ pub fn get_file_type(metadata: &fs::Metadata) -> String {
     }
 }
 
+/// Returns the inode change time (ctime), which `std` does not expose as a `SystemTime`.
+///
+/// # Arguments
+///
+/// * `metadata` - The file's metadata
+///
+/// # Returns
+///
+/// The time of the last status change, or None if it cannot be represented
+pub fn changed_time(metadata: &fs::Metadata) -> Option<SystemTime> {
+    let seconds = u64::try_from(metadata.ctime()).ok()?;
+    let nanos = u32::try_from(metadata.ctime_nsec()).ok()?;
+    UNIX_EPOCH.checked_add(Duration::new(seconds, nanos))
+}
+
+/// Badge marking entries modified within the `--highlight-new` window
+pub const NEW_BADGE: &str = "●";
+
+/// Checks whether an entry was modified within the given duration.
+///
+/// Modification times in the future (e.g. from clock skew between a build
+/// host and an NFS server) count as recent.
+///
+/// # Arguments
+///
+/// * `metadata` - The file's metadata
+/// * `within` - How far back a modification counts as recent
+///
+/// # Returns
+///
+/// `true` if the entry was modified less than `within` ago
+pub fn is_recently_modified(metadata: &fs::Metadata, within: Duration) -> bool {
+    metadata
+        .modified()
+        .is_ok_and(|modified| SystemTime::now().duration_since(modified).map_or(true, |age| age < within))
+}
+
 /// Formats a permission group (3 bits) into human-readable text.
 ///
 /// # Arguments
@@ -264,33 +477,34 @@ fn get_other_permissions(metadata: &fs::Metadata) -> String {
     format_permission_group(other_perm)
 }
 
-fn get_owner_info(metadata: &fs::Metadata) -> String {
-    let uid = metadata.uid();
-    let gid = metadata.gid();
-
-    let user_name = get_user_by_uid(uid)
+/// Resolves a user ID to its user name.
+///
+/// # Arguments
+///
+/// * `uid` - The numeric user ID
+///
+/// # Returns
+///
+/// The user name, or the numeric ID if it has no entry in the user database
+pub fn user_name(uid: u32) -> String {
+    get_user_by_uid(uid)
         .map(|user| user.name().to_string_lossy().to_string())
-        .unwrap_or_else(|| uid.to_string());
-
-    let group_name = get_group_by_gid(gid)
-        .map(|group| group.name().to_string_lossy().to_string())
-        .unwrap_or_else(|| gid.to_string());
-
-    format!("{}/{}", user_name, group_name)
+        .unwrap_or_else(|| uid.to_string())
 }
 
-/// Counts the number of items in a directory by name.
+/// Resolves a group ID to its group name.
 ///
 /// # Arguments
 ///
-/// * `dir_name` - The name of the directory to count items in
+/// * `gid` - The numeric group ID
 ///
 /// # Returns
 ///
-/// A Result containing the count as a string, or an error if the directory cannot be read.
-fn count_directory_items(dir_name: &str) -> Result<String, std::io::Error> {
-    let count = fs::read_dir(dir_name)?.count();
-    Ok(count.to_string())
+/// The group name, or the numeric ID if it has no entry in the group database
+pub fn group_name(gid: u32) -> String {
+    get_group_by_gid(gid)
+        .map(|group| group.name().to_string_lossy().to_string())
+        .unwrap_or_else(|| gid.to_string())
 }
 
 /// Counts the number of items in a directory by path.
@@ -0,0 +1,287 @@
+//! Entry filtering shared by all display formats.
+//!
+//! This module decides which directory entries are listed, so that simple,
+//! table, and tree output apply exactly the same rules: hidden-file visibility,
+//! backup-file patterns, `.gitignore` rules, `--glob` and `--regex` name
+//! patterns, relative modification-age limits, ownership, the executables-only
+//! and directories-only filters, and the `--contains` content filter.
+
+use glob::Pattern;
+use regex::bytes::Regex;
+use std::fs::{self, File};
+use std::io::Read;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tracing::debug;
+
+use crate::config::Config;
+use crate::file_info::{group_name, is_executable, user_name};
+use crate::git_ignore;
+use crate::virtual_fs::is_virtual;
+
+/// Name patterns hidden by `-B/--ignore-backups`, in addition to `--backup-pattern`
+pub const DEFAULT_BACKUP_PATTERNS: &[&str] = &["*~", "*.swp", ".#*", "*.tmp"];
+
+/// Files larger than this are never searched by `--contains`
+pub const CONTAINS_MAX_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Number of leading bytes checked for NUL bytes to detect binary files
+const BINARY_CHECK_LEN: usize = 8192;
+
+/// Checks whether an entry name is visible under the hidden-file, `--exclude`,
+/// and backup settings.
+///
+/// # Arguments
+///
+/// * `file_name` - The name of the entry
+/// * `config` - Configuration specifying whether hidden and backup files are shown
+///
+/// # Returns
+///
+/// `true` if the entry is neither hidden, excluded, nor an ignored backup file
+pub fn is_visible(file_name: &str, config: &Config) -> bool {
+    if !config.show_hidden && file_name.starts_with('.') {
+        debug!("skipping {}: hidden (use -a to show)", file_name);
+        return false;
+    }
+    if let Some(pattern) = config.exclude_patterns.iter().find(|pattern| pattern.matches(file_name)) {
+        debug!("skipping {}: matches --exclude {}", file_name, pattern);
+        return false;
+    }
+    if config.ignore_backups {
+        if let Some(pattern) = config.backup_patterns.iter().find(|pattern| pattern.matches(file_name)) {
+            debug!("skipping {}: matches backup pattern {}", file_name, pattern);
+            return false;
+        }
+    }
+    true
+}
+
+/// Checks whether an entry is hidden by `--git-ignore`.
+///
+/// Unlike the other filters, this also applies to the directories of the tree
+/// view and `-R`, so ignored trees such as `target/` are not descended into.
+///
+/// # Arguments
+///
+/// * `path` - Path to the entry
+/// * `is_dir` - Whether the entry is a directory
+/// * `config` - Configuration specifying whether gitignore rules apply
+///
+/// # Returns
+///
+/// `true` if `--git-ignore` is given and the entry matches the gitignore rules
+pub fn is_git_ignored(path: &Path, is_dir: bool, config: &Config) -> bool {
+    config.git_ignore && git_ignore::is_ignored(path, is_dir)
+}
+
+/// Checks whether an entry name matches the `--glob` and `--regex` patterns.
+///
+/// With several `--glob` patterns, matching any one of them is enough; with
+/// both options, the name must satisfy each.
+///
+/// # Arguments
+///
+/// * `file_name` - The name of the entry
+/// * `config` - Configuration holding the name patterns
+///
+/// # Returns
+///
+/// `true` if no name pattern is set or the name matches
+pub fn matches_name(file_name: &str, config: &Config) -> bool {
+    if !config.name_globs.is_empty() && !config.name_globs.iter().any(|glob| glob.matches(file_name)) {
+        debug!("skipping {}: matches no --glob pattern", file_name);
+        return false;
+    }
+    if config.name_regex.as_ref().is_some_and(|regex| !regex.is_match(file_name)) {
+        debug!("skipping {}: does not match --regex", file_name);
+        return false;
+    }
+    true
+}
+
+/// Checks whether an entry's name patterns and metadata satisfy the configured
+/// filters.
+///
+/// The name must match the `--glob` and `--regex` patterns (see
+/// [`matches_name`]); like the metadata filters, they do not apply to the
+/// directories of the tree view, so matches deep in the tree stay reachable.
+/// Age filters compare the time since last modification against the
+/// `--newer-than` and `--older-than` limits. Entries whose modification time
+/// is unavailable are excluded whenever an age filter is active. With
+/// `--executables`, only non-directory entries with an execute bit pass; with
+/// `-d`, only directories (not symlinks to them). `--user`/`--group` match the
+/// owner by name or numeric ID.
+///
+/// # Arguments
+///
+/// * `file_name` - The name of the entry, matched against the name patterns
+/// * `metadata` - The entry's metadata
+/// * `config` - Configuration specifying the active filters
+///
+/// # Returns
+///
+/// `true` if the entry passes the name patterns and every metadata-based filter
+pub fn matches_metadata(file_name: &str, metadata: &fs::Metadata, config: &Config) -> bool {
+    if !matches_name(file_name, config) {
+        return false;
+    }
+
+    if config.executables_only && (metadata.is_dir() || !is_executable(metadata)) {
+        debug!("skipping {}: not an executable file", file_name);
+        return false;
+    }
+    if config.dirs_only && !metadata.is_dir() {
+        debug!("skipping {}: not a directory", file_name);
+        return false;
+    }
+
+    if let Some(user) = &config.owner_user {
+        if !owner_matches(user, metadata.uid(), user_name) {
+            debug!("skipping {}: not owned by user {}", file_name, user);
+            return false;
+        }
+    }
+    if let Some(group) = &config.owner_group {
+        if !owner_matches(group, metadata.gid(), group_name) {
+            debug!("skipping {}: not owned by group {}", file_name, group);
+            return false;
+        }
+    }
+
+    if config.newer_than.is_none() && config.older_than.is_none() {
+        return true;
+    }
+
+    let Some(age) = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+    else {
+        // Modified in the future (clock skew) or unavailable: treat as brand new if known
+        return metadata.modified().is_ok() && config.older_than.is_none();
+    };
+
+    let age_display = humantime::format_duration(Duration::from_secs(age.as_secs()));
+    if config.newer_than.is_some_and(|limit| age > limit) {
+        debug!("skipping {}: modified {} ago, outside --newer-than", file_name, age_display);
+        return false;
+    }
+    if config.older_than.is_some_and(|limit| age < limit) {
+        debug!("skipping {}: modified {} ago, within --older-than", file_name, age_display);
+        return false;
+    }
+    true
+}
+
+/// Checks whether an owner ID matches a requested name or numeric ID.
+fn owner_matches(requested: &str, id: u32, name_of: fn(u32) -> String) -> bool {
+    requested.parse::<u32>().is_ok_and(|requested_id| requested_id == id) || name_of(id) == requested
+}
+
+/// Checks whether an entry passes all filters (name visibility and metadata).
+///
+/// # Arguments
+///
+/// * `file_name` - The name of the entry
+/// * `metadata` - The entry's metadata
+/// * `config` - Configuration specifying the active filters
+///
+/// # Returns
+///
+/// `true` if the entry should be listed
+pub fn matches(file_name: &str, metadata: &fs::Metadata, config: &Config) -> bool {
+    is_visible(file_name, config) && matches_metadata(file_name, metadata, config)
+}
+
+/// Checks whether a file's contents match the `--contains` pattern.
+///
+/// Only regular files are searched. Files larger than
+/// [`CONTAINS_MAX_FILE_SIZE`] and binary files (a NUL byte near the start)
+/// are skipped and therefore never match.
+///
+/// # Arguments
+///
+/// * `path` - Path to the entry
+/// * `metadata` - The entry's metadata
+/// * `config` - Configuration holding the content pattern
+///
+/// # Returns
+///
+/// `true` if no pattern is set or the file contains a match
+pub fn matches_contents(path: &Path, metadata: &fs::Metadata, config: &Config) -> bool {
+    let Some(pattern) = &config.contains else {
+        return true;
+    };
+    if !metadata.is_file() {
+        debug!("skipping {}: not a regular file, --contains only searches files", path.display());
+        return false;
+    }
+    if is_virtual(path) {
+        debug!("skipping {}: on a virtual filesystem, reading it could block", path.display());
+        return false;
+    }
+    if metadata.len() > CONTAINS_MAX_FILE_SIZE {
+        debug!("skipping {}: too large to search for --contains", path.display());
+        return false;
+    }
+
+    let mut contents = Vec::new();
+    if let Err(e) = File::open(path).and_then(|file| file.take(CONTAINS_MAX_FILE_SIZE).read_to_end(&mut contents)) {
+        debug!("skipping {}: cannot read contents: {}", path.display(), e);
+        return false;
+    }
+    if contents[..contents.len().min(BINARY_CHECK_LEN)].contains(&0) {
+        debug!("skipping {}: binary file", path.display());
+        return false;
+    }
+    if !pattern.is_match(&contents) {
+        debug!("skipping {}: contents do not match --contains", path.display());
+        return false;
+    }
+    true
+}
+
+/// Compiles a `--glob`, `--exclude`, or `--backup-pattern` name pattern.
+///
+/// The syntax is the one of path arguments: `*` matches any run of
+/// characters, `?` one character, and `[...]` one of a set or range.
+///
+/// # Arguments
+///
+/// * `pattern` - A shell wildcard pattern, matched against the whole name
+///
+/// # Errors
+///
+/// Returns a description of the problem if the pattern is not a valid glob,
+/// such as an unclosed `[`.
+pub fn parse_name_glob(pattern: &str) -> Result<Pattern, String> {
+    Pattern::new(pattern).map_err(|e| format!("invalid pattern: {}", e))
+}
+
+/// Compiles the `--regex` name pattern.
+///
+/// # Arguments
+///
+/// * `pattern` - A regular expression, matched anywhere in the name unless anchored
+///
+/// # Errors
+///
+/// Returns a description of the problem if the pattern is not a valid regular expression.
+pub fn parse_name_regex(pattern: &str) -> Result<regex::Regex, String> {
+    regex::Regex::new(pattern).map_err(|e| format!("invalid pattern: {}", e))
+}
+
+/// Compiles the `--contains` pattern.
+///
+/// # Arguments
+///
+/// * `pattern` - A plain string or regular expression
+///
+/// # Errors
+///
+/// Returns a description of the problem if the pattern is not a valid regular expression.
+pub fn parse_contains_pattern(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| format!("invalid pattern: {}", e))
+}
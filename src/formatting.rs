@@ -2,11 +2,97 @@
 //!
 //! This module provides functions for formatting various file attributes
 //! into human-readable strings, including file sizes, timestamps, and
-//! permission values.
+//! permission values. Month names and digit grouping follow the locale.
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, Locale, Utc};
+use chrono_tz::Tz;
 use std::fs;
-use std::os::unix::fs::PermissionsExt;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::time::SystemTime;
+
+/// Time zone used when rendering timestamps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DisplayTimeZone {
+    /// The local time zone of the machine running the command (default)
+    #[default]
+    Local,
+    /// Coordinated Universal Time
+    Utc,
+    /// A named IANA time zone such as "Europe/Berlin"
+    Named(Tz),
+}
+
+/// Locales used when rendering month names and exact numbers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DisplayLocale {
+    /// Locale of month names in timestamps (`LC_TIME`)
+    pub time: Locale,
+    /// Locale of digit grouping in exact sizes (`LC_NUMERIC`)
+    pub numeric: Locale,
+}
+
+impl DisplayLocale {
+    /// Reads the locales from the environment.
+    ///
+    /// Each category follows the POSIX precedence: `LC_ALL`, then the
+    /// category's own variable (`LC_TIME` or `LC_NUMERIC`), then `LANG`.
+    /// Unset or unknown locales fall back to POSIX (English month names).
+    ///
+    /// # Returns
+    ///
+    /// The locales of the environment
+    pub fn from_env() -> Self {
+        let category = |name: &str| {
+            ["LC_ALL", name, "LANG"]
+                .iter()
+                .filter_map(|variable| std::env::var(variable).ok())
+                .find(|value| !value.is_empty())
+                .and_then(|value| parse_locale(&value))
+                .unwrap_or_default()
+        };
+        Self {
+            time: category("LC_TIME"),
+            numeric: category("LC_NUMERIC"),
+        }
+    }
+
+    /// Uses one locale for every category, as `--locale` does.
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - The locale to use
+    ///
+    /// # Returns
+    ///
+    /// The same locale for month names and digit grouping
+    pub fn uniform(locale: Locale) -> Self {
+        Self { time: locale, numeric: locale }
+    }
+}
+
+/// Parses a locale name as found in `LANG`, such as "de_DE", "de_DE.UTF-8",
+/// or "de_DE.UTF-8@euro".
+///
+/// The codeset is ignored, and a modifier is dropped if the locale has no
+/// variant for it. "C" is the same as "POSIX".
+///
+/// # Arguments
+///
+/// * `name` - The locale name
+///
+/// # Returns
+///
+/// The locale, or None if it is unknown
+pub fn parse_locale(name: &str) -> Option<Locale> {
+    let (name, modifier) = name.split_once('@').map_or((name, None), |(name, modifier)| (name, Some(modifier)));
+    let language = name.split('.').next().unwrap_or_default();
+    if matches!(language, "C" | "POSIX") {
+        return Some(Locale::POSIX);
+    }
+    modifier
+        .and_then(|modifier| Locale::try_from(format!("{}@{}", language, modifier).as_str()).ok())
+        .or_else(|| Locale::try_from(language).ok())
+}
 
 /// Formats a file size in bytes into a human-readable string.
 ///
@@ -39,23 +125,172 @@ pub fn format_size(size: u64) -> String {
     }
 }
 
-/// Formats the modification time from file metadata into a readable string.
+/// Parses a human-readable size such as "5MB", "1.5G", or "4096".
+///
+/// Units are powers of 1024, like those of [`format_size`]: `K`, `M`, `G`,
+/// and `T`, optionally followed by `B` or `iB` and in either case. A number
+/// without a unit is a count of bytes.
 ///
 /// # Arguments
 ///
-/// * `metadata` - The file's metadata
+/// * `text` - The size to parse
 ///
 /// # Returns
 ///
-/// A formatted timestamp string like "Jun 08 14:30" or "Unknown" if unavailable
-pub fn format_time(metadata: &fs::Metadata) -> String {
-    match metadata.modified() {
-        Ok(time) => {
-            let datetime: DateTime<Local> = time.into();
-            datetime.format("%b %d %H:%M").to_string()
-        }
-        Err(_) => "Unknown".to_string(),
+/// The size in bytes, or None if the text is not a size
+pub fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let unit = unit.trim().to_ascii_uppercase();
+    let exponent = match unit.trim_end_matches("IB").trim_end_matches('B') {
+        "" => 0,
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        _ => return None,
+    };
+    Some((number * 1024f64.powi(exponent)) as u64)
+}
+
+/// Formats an optional timestamp into the short listing format.
+///
+/// # Arguments
+///
+/// * `time` - The point in time to format, or None if the platform doesn't provide it
+/// * `time_zone` - The time zone to render the timestamp in
+/// * `locale` - The locale of the month abbreviation
+///
+/// # Returns
+///
+/// A formatted timestamp string like "Jun 08 14:30" (or "Jun. 08 14:30" in
+/// `de_DE`) or "Unknown" if unavailable
+pub fn format_short_time(time: Option<SystemTime>, time_zone: DisplayTimeZone, locale: Locale) -> String {
+    match time {
+        Some(time) => format_in_time_zone(time, time_zone, locale, "%b %d %H:%M"),
+        None => "Unknown".to_string(),
+    }
+}
+
+/// Seconds in six months of an average Gregorian year, the age at which
+/// `ls -l` shows the year instead of the time of day
+const SIX_MONTHS_SECS: u64 = 31_556_952 / 2;
+
+/// Formats an optional timestamp like `ls -l` does.
+///
+/// Timestamps from the last six months show the time of day; older ones, and
+/// ones in the future, show the year instead. Days are padded with a space.
+///
+/// # Arguments
+///
+/// * `time` - The point in time to format, or None if the platform doesn't provide it
+/// * `time_zone` - The time zone to render the timestamp in
+/// * `locale` - The locale of the month abbreviation
+///
+/// # Returns
+///
+/// A timestamp string like "Jun  8 14:30" or "Jun  8  2023", or "?" if unavailable
+pub fn format_classic_time(time: Option<SystemTime>, time_zone: DisplayTimeZone, locale: Locale) -> String {
+    let Some(time) = time else {
+        return "?".to_string();
+    };
+    let recent = SystemTime::now()
+        .duration_since(time)
+        .is_ok_and(|age| age.as_secs() < SIX_MONTHS_SECS);
+    let format = if recent { "%b %e %H:%M" } else { "%b %e  %Y" };
+    format_in_time_zone(time, time_zone, locale, format)
+}
+
+/// Formats an optional timestamp as a fixed-width UTC timestamp.
+///
+/// The format does not depend on the local time zone or locale, so the same
+/// file always renders the same way.
+///
+/// # Arguments
+///
+/// * `time` - The point in time to format, or None if unavailable
+///
+/// # Returns
+///
+/// A timestamp string like "2024-06-08T12:30:00Z" or "Unknown" if unavailable
+pub fn format_utc_time(time: Option<SystemTime>) -> String {
+    match time {
+        Some(time) => format_in_time_zone(time, DisplayTimeZone::Utc, Locale::POSIX, "%Y-%m-%dT%H:%M:%SZ"),
+        None => "Unknown".to_string(),
+    }
+}
+
+/// Formats an exact file size in bytes with the locale's digit grouping.
+///
+/// Locales without digit grouping (such as POSIX) fall back to groups of three
+/// separated by commas, so exact sizes stay readable.
+///
+/// # Arguments
+///
+/// * `size` - The file size in bytes
+/// * `locale` - The numeric locale: a period separates groups in `de_DE`, a
+///   narrow no-break space in `fr_FR`, and `en_IN` groups lakhs and crores
+///
+/// # Returns
+///
+/// A formatted string like "1,536,224"
+pub fn format_bytes(size: u64, locale: Locale) -> String {
+    let digits = size.to_string();
+    let (separator, grouping) = match pure_rust_locales::locale_match!(locale => LC_NUMERIC::THOUSANDS_SEP) {
+        "" => (",", &[3][..]),
+        separator => (separator, pure_rust_locales::locale_match!(locale => LC_NUMERIC::GROUPING)),
+    };
+
+    // Group sizes from the right; the last one repeats, and -1 or 0 stops grouping
+    let mut groups = Vec::new();
+    let mut rest = digits.as_str();
+    let mut sizes = grouping.iter().copied().chain(std::iter::repeat(*grouping.last().unwrap_or(&3)));
+    while let Some(size) = sizes.next().filter(|&size| size > 0 && (size as usize) < rest.len()) {
+        let (head, group) = rest.split_at(rest.len() - size as usize);
+        groups.push(group);
+        rest = head;
     }
+    groups.push(rest);
+    groups.reverse();
+    groups.join(separator)
+}
+
+/// Formats the time elapsed since a timestamp as a compact age.
+///
+/// The largest fitting unit is used: seconds (`s`), minutes (`m`), hours
+/// (`h`), days (`d`), 30-day months (`mo`), or 365-day years (`y`).
+///
+/// # Arguments
+///
+/// * `time` - The point in time to measure from, or None if unavailable
+///
+/// # Returns
+///
+/// An age like "45s", "3d", "7mo", or "2y"; "0s" for times in the future
+/// and "Unknown" if unavailable
+pub fn format_age(time: Option<SystemTime>) -> String {
+    const UNITS: &[(u64, &str)] = &[
+        (365 * 24 * 60 * 60, "y"),
+        (30 * 24 * 60 * 60, "mo"),
+        (24 * 60 * 60, "d"),
+        (60 * 60, "h"),
+        (60, "m"),
+    ];
+
+    let Some(time) = time else {
+        return "Unknown".to_string();
+    };
+    let seconds = SystemTime::now()
+        .duration_since(time)
+        .map_or(0, |age| age.as_secs());
+
+    UNITS
+        .iter()
+        .find(|(unit, _)| seconds >= *unit)
+        .map(|(unit, suffix)| format!("{}{}", seconds / unit, suffix))
+        .unwrap_or_else(|| format!("{}s", seconds))
 }
 
 /// Formats file permissions as an octal string.
@@ -70,4 +305,164 @@ pub fn format_time(metadata: &fs::Metadata) -> String {
 pub fn format_octal_permissions(metadata: &fs::Metadata) -> String {
     let mode = metadata.permissions().mode();
     format!("{:o}", mode & 0o7777)
-}
\ No newline at end of file
+}
+
+/// Formats file permissions as a traditional `ls -l` style symbolic string.
+///
+/// The first character identifies the file type (`d`, `l`, `c`, `b`, `p`,
+/// `s`, or `-`), followed by three `rwx` triplets. Special bits replace the
+/// execute position: `s`/`S` for setuid and setgid, and `t`/`T` for the
+/// sticky bit, lowercase when execute is also set.
+///
+/// # Arguments
+///
+/// * `metadata` - The file's metadata (use `symlink_metadata` to detect symlinks)
+///
+/// # Returns
+///
+/// A symbolic permission string like "drwxr-xr-x" or "-rwsr-xr-x"
+pub fn format_symbolic_permissions(metadata: &fs::Metadata) -> String {
+    let mode = metadata.permissions().mode();
+    let file_type = metadata.file_type();
+
+    let mut result = String::with_capacity(10);
+    result.push(if file_type.is_symlink() {
+        'l'
+    } else if file_type.is_dir() {
+        'd'
+    } else if file_type.is_char_device() {
+        'c'
+    } else if file_type.is_block_device() {
+        'b'
+    } else if file_type.is_fifo() {
+        'p'
+    } else if file_type.is_socket() {
+        's'
+    } else {
+        '-'
+    });
+
+    let triplets = [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')];
+    for (shift, special_bit, special_char) in triplets {
+        let perm = (mode >> shift) & 7;
+        result.push(if perm & 4 != 0 { 'r' } else { '-' });
+        result.push(if perm & 2 != 0 { 'w' } else { '-' });
+
+        let executable = perm & 1 != 0;
+        result.push(match (mode & special_bit != 0, executable) {
+            (true, true) => special_char,
+            (true, false) => special_char.to_ascii_uppercase(),
+            (false, true) => 'x',
+            (false, false) => '-',
+        });
+    }
+
+    result
+}
+
+/// Explains an entry's permissions in plain words, for newcomers to octal modes.
+///
+/// Directory bits are explained with their directory meanings (read lists,
+/// write creates and deletes entries, execute enters), and the setuid, setgid,
+/// and sticky bits with what they do for the entry's type.
+///
+/// # Arguments
+///
+/// * `metadata` - The entry's metadata (use `symlink_metadata` to detect symlinks)
+///
+/// # Returns
+///
+/// An explanation like "2755 (drwxr-sr-x): owner can list, create and delete
+/// entries, and enter; group can list and enter; others can list and enter;
+/// setgid: new entries inherit the directory's group"
+pub fn explain_permissions(metadata: &fs::Metadata) -> String {
+    let mode = metadata.permissions().mode();
+    let is_dir = metadata.is_dir();
+    let abilities: [&str; 3] = if is_dir {
+        ["list", "create and delete entries", "enter"]
+    } else {
+        ["read", "write", "execute"]
+    };
+
+    let mut parts: Vec<String> = [("owner", 6), ("group", 3), ("others", 0)]
+        .into_iter()
+        .map(|(class, shift)| {
+            let bits = (mode >> shift) & 7;
+            let granted: Vec<&str> = [4, 2, 1]
+                .into_iter()
+                .zip(abilities)
+                .filter(|(bit, _)| bits & bit != 0)
+                .map(|(_, ability)| ability)
+                .collect();
+            match granted.as_slice() {
+                [] if class == "others" => "others have no access".to_string(),
+                [] => format!("{} has no access", class),
+                [only] => format!("{} can {}", class, only),
+                [first, second] => format!("{} can {} and {}", class, first, second),
+                [rest @ .., last] => format!("{} can {}, and {}", class, rest.join(", "), last),
+            }
+        })
+        .collect();
+
+    if metadata.file_type().is_symlink() {
+        parts.push("symlink permissions are not used; the target's apply".to_string());
+    } else {
+        let special_bits = [
+            (0o4000, "setuid", if is_dir { "ignored on directories" } else { "runs with the owner's privileges" }),
+            (0o2000, "setgid", if is_dir { "new entries inherit the directory's group" } else { "runs with the group's privileges" }),
+            (0o1000, "sticky", if is_dir { "only owners can delete or rename their entries" } else { "ignored on files" }),
+        ];
+        parts.extend(
+            special_bits
+                .into_iter()
+                .filter(|(bit, _, _)| mode & bit != 0)
+                .map(|(_, name, meaning)| format!("{}: {}", name, meaning)),
+        );
+    }
+
+    format!(
+        "{} ({}): {}",
+        format_octal_permissions(metadata),
+        format_symbolic_permissions(metadata),
+        parts.join("; ")
+    )
+}
+
+/// Formats a timestamp with full date, time, sub-second precision, and UTC offset.
+///
+/// # Arguments
+///
+/// * `time` - The point in time to format
+/// * `time_zone` - The time zone to render the timestamp in
+///
+/// # Returns
+///
+/// A formatted timestamp string like "2024-06-08 14:30:12.123456789 +0200"
+pub fn format_timestamp(time: SystemTime, time_zone: DisplayTimeZone) -> String {
+    format_in_time_zone(time, time_zone, Locale::POSIX, "%Y-%m-%d %H:%M:%S%.9f %z")
+}
+
+/// Formats a timestamp as ISO-8601 with the UTC offset, for machine-readable output.
+///
+/// # Arguments
+///
+/// * `time` - The point in time to format
+/// * `time_zone` - The time zone to render the timestamp in
+///
+/// # Returns
+///
+/// A timestamp string like "2024-06-08T14:30:12+02:00"
+pub fn format_iso8601(time: SystemTime, time_zone: DisplayTimeZone) -> String {
+    format_in_time_zone(time, time_zone, Locale::POSIX, "%Y-%m-%dT%H:%M:%S%:z")
+}
+
+/// Renders a point in time with a chrono format string in the given time zone,
+/// with names of months and days in the given locale.
+fn format_in_time_zone(time: SystemTime, time_zone: DisplayTimeZone, locale: Locale, format: &str) -> String {
+    let utc: DateTime<Utc> = time.into();
+    match time_zone {
+        DisplayTimeZone::Local => utc.with_timezone(&Local).format_localized(format, locale).to_string(),
+        DisplayTimeZone::Utc => utc.format_localized(format, locale).to_string(),
+        DisplayTimeZone::Named(tz) => utc.with_timezone(&tz).format_localized(format, locale).to_string(),
+    }
+}
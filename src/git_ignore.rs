@@ -0,0 +1,134 @@
+//! Gitignore-aware filtering for `--git-ignore`.
+//!
+//! An entry is ignored when it matches the `.gitignore` or `.ignore` files of
+//! its directory or any directory above it up to the repository root, the
+//! repository's `.git/info/exclude`, or the global excludes file
+//! (`core.excludesFile`). As in git, rules in deeper directories take
+//! precedence and `!pattern` re-includes an entry.
+//!
+//! The rules of each directory are read once per run and cached, since tree
+//! and recursive listings check every entry of every directory they visit.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::{debug, warn};
+
+/// Per-directory ignore files, in increasing order of precedence
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore"];
+
+/// The ignore rules that apply to the entries of one directory.
+struct Rules {
+    /// The directory, canonicalized so it can be matched against the matchers' roots
+    directory: PathBuf,
+    /// Matchers from the directory up to the repository root, deepest first
+    matchers: Vec<Arc<Gitignore>>,
+}
+
+/// Returns the process-wide cache of the rules of each listed directory.
+fn rules_cache() -> &'static Mutex<HashMap<PathBuf, Arc<Rules>>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, Arc<Rules>>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Returns the process-wide cache of the matchers built from each directory's own files.
+fn matcher_cache() -> &'static Mutex<HashMap<PathBuf, Arc<Gitignore>>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, Arc<Gitignore>>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Returns the global excludes matcher, read once per run.
+fn global_matcher() -> Arc<Gitignore> {
+    static GLOBAL: OnceLock<Arc<Gitignore>> = OnceLock::new();
+    GLOBAL
+        .get_or_init(|| {
+            let (matcher, error) = Gitignore::global();
+            if let Some(e) = error {
+                warn!("ignoring invalid global git excludes: {}", e);
+            }
+            Arc::new(matcher)
+        })
+        .clone()
+}
+
+/// Checks whether an entry is excluded by the gitignore rules that apply to it.
+///
+/// # Arguments
+///
+/// * `path` - Path to the entry
+/// * `is_dir` - Whether the entry is a directory, for patterns ending in `/`
+///
+/// # Returns
+///
+/// `true` if the entry is ignored and not re-included by a later `!pattern`
+pub fn is_ignored(path: &Path, is_dir: bool) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let rules = rules_for(directory);
+    let path = rules.directory.join(name);
+
+    let global = global_matcher();
+    for matcher in rules.matchers.iter().chain(std::iter::once(&global)) {
+        match matcher.matched(&path, is_dir) {
+            Match::Ignore(glob) => {
+                debug!("skipping {}: ignored by {}", path.display(), glob.original());
+                return true;
+            }
+            Match::Whitelist(_) => return false,
+            Match::None => {}
+        }
+    }
+    false
+}
+
+/// Returns the rules of a directory, reading its ignore files and those of
+/// its ancestors on first use.
+fn rules_for(directory: &Path) -> Arc<Rules> {
+    let mut cache = rules_cache().lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry(directory.to_path_buf())
+        .or_insert_with(|| {
+            let directory = fs::canonicalize(directory).unwrap_or_else(|_| directory.to_path_buf());
+            let mut matchers = Vec::new();
+            for ancestor in directory.ancestors() {
+                matchers.push(own_matcher(ancestor));
+                // Rules above the repository do not apply to it
+                if ancestor.join(".git").exists() {
+                    break;
+                }
+            }
+            Arc::new(Rules { directory, matchers })
+        })
+        .clone()
+}
+
+/// Returns the matcher built from a directory's own ignore files, and from
+/// `.git/info/exclude` at a repository root.
+fn own_matcher(directory: &Path) -> Arc<Gitignore> {
+    let mut cache = matcher_cache().lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry(directory.to_path_buf())
+        .or_insert_with(|| {
+            let mut builder = GitignoreBuilder::new(directory);
+            let exclude = directory.join(".git").join("info").join("exclude");
+            let files = std::iter::once(exclude).chain(IGNORE_FILES.iter().map(|name| directory.join(name)));
+            for file in files.filter(|file| file.is_file()) {
+                if let Some(e) = builder.add(&file) {
+                    warn!("ignoring invalid rules in {}: {}", file.display(), e);
+                }
+            }
+            Arc::new(builder.build().unwrap_or_else(|e| {
+                warn!("ignoring ignore files of {}: {}", directory.display(), e);
+                Gitignore::empty()
+            }))
+        })
+        .clone()
+}
@@ -0,0 +1,246 @@
+//! Nerd Font icons for `--icons`.
+//!
+//! Each entry is shown with a glyph for its file type, like `exa`/`eza`.
+//! Exact file names (`Cargo.toml`, `.git`) are looked up first, then
+//! extensions, then the type of the entry. The glyphs are in the private use
+//! area and need a [Nerd Font](https://www.nerdfonts.com) in the terminal.
+//!
+//! The `[icons]` section of the configuration file overrides or adds icons,
+//! with any string (an emoji works too) as the icon:
+//!
+//! ```toml
+//! [icons.names]
+//! Justfile = "\uf489"
+//!
+//! [icons.extensions]
+//! rs = "🦀"
+//!
+//! [icons.types]
+//! directory = "📁"
+//! ```
+//!
+//! Names match exactly, and extensions case-insensitively with or without the
+//! leading dot. Types are `directory`, `file`, `executable`, `symlink`,
+//! `broken-symlink`, `fifo`, `socket`, and `device`.
+
+use std::collections::HashMap;
+use tracing::warn;
+
+use crate::config::Config;
+
+/// Icons of well-known file and directory names
+const NAME_ICONS: &[(&str, char)] = &[
+    (".git", '\u{e5fb}'),
+    (".gitattributes", '\u{f1d3}'),
+    (".github", '\u{e5fd}'),
+    (".gitignore", '\u{f1d3}'),
+    (".gitmodules", '\u{f1d3}'),
+    ("Cargo.lock", '\u{e7a8}'),
+    ("Cargo.toml", '\u{e7a8}'),
+    ("Dockerfile", '\u{f308}'),
+    ("LICENSE", '\u{f02d}'),
+    ("Makefile", '\u{f489}'),
+    ("node_modules", '\u{e5fa}'),
+];
+
+/// Icons by lowercase extension
+const EXTENSION_ICONS: &[(&str, char)] = &[
+    ("7z", '\u{f410}'),
+    ("bash", '\u{f489}'),
+    ("bmp", '\u{f1c5}'),
+    ("bz2", '\u{f410}'),
+    ("c", '\u{e61e}'),
+    ("cpp", '\u{e61d}'),
+    ("css", '\u{e749}'),
+    ("flac", '\u{f001}'),
+    ("gif", '\u{f1c5}'),
+    ("go", '\u{e626}'),
+    ("gz", '\u{f410}'),
+    ("h", '\u{f0fd}'),
+    ("html", '\u{f13b}'),
+    ("ico", '\u{f1c5}'),
+    ("java", '\u{e256}'),
+    ("jpeg", '\u{f1c5}'),
+    ("jpg", '\u{f1c5}'),
+    ("js", '\u{e74e}'),
+    ("json", '\u{e60b}'),
+    ("lock", '\u{f023}'),
+    ("log", '\u{f18d}'),
+    ("md", '\u{f48a}'),
+    ("mkv", '\u{f03d}'),
+    ("mov", '\u{f03d}'),
+    ("mp3", '\u{f001}'),
+    ("mp4", '\u{f03d}'),
+    ("ogg", '\u{f001}'),
+    ("pdf", '\u{f1c1}'),
+    ("png", '\u{f1c5}'),
+    ("py", '\u{e606}'),
+    ("rar", '\u{f410}'),
+    ("rb", '\u{e21e}'),
+    ("rs", '\u{e7a8}'),
+    ("sh", '\u{f489}'),
+    ("svg", '\u{f1c5}'),
+    ("tar", '\u{f410}'),
+    ("toml", '\u{e615}'),
+    ("ts", '\u{e628}'),
+    ("txt", '\u{f15c}'),
+    ("wav", '\u{f001}'),
+    ("webm", '\u{f03d}'),
+    ("webp", '\u{f1c5}'),
+    ("xz", '\u{f410}'),
+    ("yaml", '\u{e615}'),
+    ("yml", '\u{e615}'),
+    ("zip", '\u{f410}'),
+    ("zsh", '\u{f489}'),
+    ("zst", '\u{f410}'),
+];
+
+/// Icons by entry type, keyed as in the `[icons.types]` section
+const TYPE_ICONS: &[(&str, char)] = &[
+    ("broken-symlink", '\u{f127}'),
+    ("device", '\u{f0a0}'),
+    ("directory", '\u{f115}'),
+    ("executable", '\u{f489}'),
+    ("fifo", '\u{f0ec}'),
+    ("file", '\u{f15b}'),
+    ("socket", '\u{f1e6}'),
+    ("symlink", '\u{f481}'),
+];
+
+/// Icons by file name, extension, and entry type.
+#[derive(Clone)]
+pub struct Icons {
+    /// Icons by exact file name
+    names: HashMap<String, String>,
+    /// Icons by lowercase extension without the dot
+    extensions: HashMap<String, String>,
+    /// Icons by entry type (see [`TYPE_ICONS`])
+    types: HashMap<String, String>,
+}
+
+impl Default for Icons {
+    fn default() -> Self {
+        let table = |icons: &[(&str, char)]| {
+            icons
+                .iter()
+                .map(|(key, icon)| (key.to_string(), icon.to_string()))
+                .collect()
+        };
+        Self {
+            names: table(NAME_ICONS),
+            extensions: table(EXTENSION_ICONS),
+            types: table(TYPE_ICONS),
+        }
+    }
+}
+
+impl Icons {
+    /// Builds the built-in icons with the overrides of the `[icons]` section
+    /// of the configuration file.
+    ///
+    /// Values that are not strings and unknown types are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The parsed configuration file (see
+    ///   [`load_config_file`](crate::config::load_config_file))
+    ///
+    /// # Returns
+    ///
+    /// The icons to use
+    pub fn from_config(file: &toml::Table) -> Self {
+        let mut icons = Self::default();
+        let Some(toml::Value::Table(section)) = file.get("icons") else {
+            return icons;
+        };
+
+        let overrides = |key: &str| {
+            let Some(toml::Value::Table(table)) = section.get(key) else {
+                return Vec::new();
+            };
+            table
+                .iter()
+                .filter_map(|(name, value)| match value {
+                    toml::Value::String(icon) => Some((name.clone(), icon.clone())),
+                    _ => {
+                        warn!("ignoring non-string icon for '{}' in the configuration file", name);
+                        None
+                    }
+                })
+                .collect()
+        };
+        icons.names.extend(overrides("names"));
+        icons.extensions.extend(
+            overrides("extensions")
+                .into_iter()
+                .map(|(extension, icon)| (extension.trim_start_matches('.').to_lowercase(), icon)),
+        );
+        for (kind, icon) in overrides("types") {
+            match icons.types.get_mut(&kind) {
+                Some(current) => *current = icon,
+                None => warn!("ignoring icon for unknown type '{}' in the configuration file", kind),
+            }
+        }
+        icons
+    }
+
+    /// Returns the icon of an entry.
+    ///
+    /// Directories, symlinks, and special files only match by exact name;
+    /// regular files and executables also by extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The entry's file name
+    /// * `file_type` - The entry's type as returned by
+    ///   [`get_file_type`](crate::file_info::get_file_type)
+    /// * `broken_link` - Whether the entry is a symlink to a missing target
+    ///
+    /// # Returns
+    ///
+    /// The icon
+    pub fn icon(&self, name: &str, file_type: &str, broken_link: bool) -> &str {
+        if let Some(icon) = self.names.get(name) {
+            return icon;
+        }
+        let kind = match file_type {
+            "Directory" => "directory",
+            "Symlink" if broken_link => "broken-symlink",
+            "Symlink" => "symlink",
+            "FIFO" => "fifo",
+            "Socket" => "socket",
+            "Block Device" | "Character Device" => "device",
+            "Executable" => "executable",
+            _ => "file",
+        };
+        if matches!(kind, "file" | "executable") {
+            let extension = name.rsplit_once('.').map(|(_, extension)| extension.to_lowercase());
+            if let Some(icon) = extension.and_then(|extension| self.extensions.get(&extension)) {
+                return icon;
+            }
+        }
+        self.types.get(kind).map_or("", String::as_str)
+    }
+}
+
+/// Returns the icon of an entry followed by a space, to be written before its
+/// name.
+///
+/// # Arguments
+///
+/// * `name` - The entry's file name
+/// * `file_type` - The entry's type as returned by
+///   [`get_file_type`](crate::file_info::get_file_type)
+/// * `broken_link` - Whether the entry is a symlink to a missing target
+/// * `config` - Configuration holding the icons, if `--icons` is set
+///
+/// # Returns
+///
+/// The prefix, or an empty string without `--icons`
+pub fn icon_prefix(name: &str, file_type: &str, broken_link: bool, config: &Config) -> String {
+    config
+        .icons
+        .as_ref()
+        .map(|icons| format!("{} ", icons.icon(name, file_type, broken_link)))
+        .unwrap_or_default()
+}
@@ -0,0 +1,40 @@
+//! Diagnostic logging setup.
+//!
+//! This module configures `tracing` output on stderr. Per-entry problems are
+//! reported as warnings by default, `-q` silences them, and `-v`/`-vv` add
+//! debug and trace messages explaining display selection and filter decisions.
+//! The `FLS_LOG` environment variable accepts a full env-filter directive and
+//! takes precedence over the flags.
+
+use std::io::{self, IsTerminal};
+use tracing_subscriber::EnvFilter;
+
+use crate::colors::color_preference;
+
+/// Environment variable holding an explicit log filter (e.g. `FLS_LOG=debug`)
+const LOG_ENV_VAR: &str = "FLS_LOG";
+
+/// Initializes the global stderr logger.
+///
+/// # Arguments
+///
+/// * `verbosity` - Number of `-v` flags given (0 = warnings, 1 = debug, 2+ = trace)
+/// * `quiet` - Whether to suppress warnings and only report errors
+pub fn init(verbosity: u8, quiet: bool) {
+    let default_level = match (quiet, verbosity) {
+        (true, _) => "error",
+        (false, 0) => "warn",
+        (false, 1) => "debug",
+        (false, _) => "trace",
+    };
+
+    let filter = EnvFilter::try_from_env(LOG_ENV_VAR).unwrap_or_else(|_| EnvFilter::new(default_level));
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr)
+        .with_ansi(color_preference().unwrap_or_else(|| io::stderr().is_terminal()))
+        .with_target(false)
+        .without_time()
+        .init();
+}
@@ -15,7 +15,7 @@
 //! ## Usage
 //!
 //! ```bash
-//! # Basic listing
+//! # Basic listing (in columns in a terminal, one name per line when piped)
 //! fls
 //!
 //! # Detailed table format
@@ -33,41 +33,289 @@
 //! # Tree view with hidden files
 //! fls -ta
 //!
+//! # Machine-readable listing for scripts
+//! fls -j | jq '.[] | select(.type == "Directory") | .name'
+//!
+//! # The ten largest files, already in order
+//! fls -j --sort size --rank | jq '.[:10][] | "\(.rank) \(.name)"'
+//!
+//! # Every subdirectory as its own table, like ls -R
+//! fls -lR -L 2 src
+//!
+//! # Several directories under headers, plus a file row (like ls -l)
+//! fls -l src tests Cargo.toml
+//!
+//! # Patterns the shell did not expand are expanded by fls
+//! fls -l "src/**/*.rs"
+//!
 //! # Interactive tree view
 //! fls -ti
 //!
 //! # Tree view with depth limit
 //! fls -t -L 2
 //!
+//! # Subdirectory and file counts after each directory name
+//! fls -t --dir-counts -L 2
+//!
+//! # The structure of a large tree, with at most 3 files per directory
+//! fls -t --collapse-files 3
+//!
 //! # Tree view with depth limit and hidden files
 //! fls -ta --depth 3
 //!
+//! # Wrap long file names in the table
+//! fls -l --wrap 30
+//!
+//! # Center the Type column (Size and Octal are right-aligned by default)
+//! fls -l --align type=center
+//!
+//! # Compact table without separators between rows
+//! fls --compact
+//!
+//! # Skim a directory of small notes
+//! fls --preview 2 notes/
+//!
+//! # Fit the table into 100 columns when piping to a file
+//! fls -l --width 100 > listing.txt
+//!
+//! # Show how much space each directory really uses
+//! fls -l --recursive-sizes
+//!
+//! # Where the space goes, two levels deep
+//! fls -t --du -L 2
+//!
+//! # Find the directories using up the filesystem's inodes
+//! fls -l --inodes --summary-row /var
+//!
+//! # A mini report: total size, disk usage, and entries per type
+//! fls -l --summary-row --aggregates size,blocks,types
+//!
+//! # Show modified, accessed, changed, and created times side by side
+//! fls -l --times all
+//!
+//! # Newest downloads first, by when they arrived rather than when last touched
+//! fls -l --sort created ~/Downloads
+//!
+//! # Largest files last, right above the prompt
+//! fls -l --sort size -r
+//!
+//! # Group by owner, newest first within each owner
+//! fls -l --sort user --tiebreak mtime
+//!
+//! # Exact sizes for verifying a transfer byte-for-byte
+//! fls -l --bytes /mnt/backup
+//!
+//! # Compare magnitudes at a glance with units in their own column
+//! fls -l --size-split /var/log
+//!
+//! # Files that still mention TODO, with their metadata
+//! fls -l --contains TODO src/
+//!
+//! # Include . and .. to see the parent directory's permissions inline
+//! fls -l --all-all
+//!
+//! # Order the table by permissions, marked with an arrow in the Octal header
+//! fls -l --sort-column octal
+//!
+//! # Audit the space used through links into large data files
+//! fls -l --link-sizes target --summary-row data/
+//!
+//! # Hover the permission cells to see what each mode means
+//! fls -li /usr/bin
+//!
+//! # Interactive tree whose directory links re-list the directory when clicked
+//! fls -t -i --dir-links fls
+//!
+//! # Check whether two copies of a tree are identical
+//! fls -l --dir-hash --all-all backup/ && fls -l --dir-hash --all-all original/
+//!
+//! # Snapshot a directory for version control; rerun and diff to see what changed
+//! fls -l --deterministic > snapshot.txt
+//!
+//! # Conventional ls -l lines for scripts that parse them
+//! fls --classic /var/log
+//!
+//! # Import a listing into a spreadsheet
+//! fls --format csv --times all ~/Documents > documents.csv
+//!
+//! # Everything a forensic review needs, without a long list of flags
+//! fls -l --preset forensics /srv/incoming
+//!
+//! # Quick visual profile of which entries are largest
+//! fls -l --heat size
+//!
+//! # Show the team's notes on a shared data directory
+//! fls -l --comments /srv/data
+//!
+//! # Shade every second row of a wide table
+//! fls -l --times all --stripe
+//!
+//! # Triage a mixed asset folder by file extension
+//! fls -l --ext --group-by ext assets/
+//!
+//! # Find sparse, compressed, and preallocated files
+//! fls -l --disk-usage-delta /var/lib/images
+//!
+//! # Compact ages, youngest first, only for files untouched for a month
+//! fls -l --age --sort age --older-than 30days
+//!
+//! # Render timestamps independently of the local time zone
+//! fls -l --utc
+//! fls -l --timezone America/New_York
+//!
+//! # German month names and digit grouping
+//! fls -l --bytes --locale de_DE
+//!
+//! # Tree of a repository without target/, node_modules/, and other ignored files
+//! fls -t --git-ignore
+//!
+//! # Only Rust sources, keeping the table formatting (no grep needed)
+//! fls -l --glob '*.rs'
+//! fls -t --regex '^test_'
+//!
+//! # File type icons (needs a Nerd Font)
+//! fls -l --icons
+//!
+//! # Skip build output, and ignore the defaults in ~/.config/fls/config.toml
+//! fls -l --exclude '*.o' --exclude target --no-config
+//!
+//! # Files touched in the last 90 minutes, or untouched for two weeks
+//! fls -l --newer-than 90min
+//! fls -l --older-than 2weeks
+//!
+//! # Project structure at a glance, without the files
+//! fls -td -L 3
+//!
+//! # Audit what is runnable in a scripts directory
+//! fls -l --executables scripts/
+//!
+//! # Files owned by www-data, sorted by group, without the redundant User column
+//! fls -l --user www-data --sort group --hide user
+//!
+//! # Make files owned by someone else stand out
+//! fls -l --smart-owner /srv
+//!
+//! # Lint for sloppy permissions against a stricter baseline
+//! fls -l --highlight-perms --expected-file-mode 640 --expected-dir-mode 750
+//!
+//! # Hide editor backups, including a project-specific pattern
+//! fls -B --backup-pattern '*.orig'
+//!
+//! # List the directory a symlink points to instead of the link itself
+//! fls -lH /path/to/link
+//!
+//! # Explain why entries were filtered out
+//! fls -v --older-than 2weeks
+//!
+//! # Detailed view of a single file
+//! fls stat Cargo.toml
+//!
+//! # Custom metadata from any command, one column per command
+//! fls -l --exec-column Kind='file -b {}' --exec-column Lines='wc -l < {}'
+//!
+//! # Trashed files with where they came from and when
+//! fls trash
+//!
+//! # Huge NFS mount on a tiny container: stream entries without sorting
+//! fls -l --low-memory /mnt/archive
+//!
+//! # Which columns and filters would this command line use?
+//! fls -l --times all --newer-than 2d --probe
+//!
+//! # Would this tree check out cleanly on macOS or Windows?
+//! fls -t --check-case-collisions
+//!
+//! # Validate file names before a cross-platform release
+//! fls -t --check-names --check-case-collisions
+//!
+//! # Files left behind by removed accounts on a shared server
+//! fls -R --check-owners /srv/shared
+//!
+//! # Re-run in a loop during a build to spot freshly written files
+//! watch -c fls -l --highlight-new 30 target/debug
+//!
+//! # Live-updating tree for a build output directory
+//! fls -t --watch target
+//!
+//! # What changed in Downloads since the last look, and how much did it grow?
+//! fls -l --changed ~/Downloads
+//!
+//! # Who changed owners or modes in /etc since the last audit run?
+//! fls -l -a --changed /etc
+//!
+//! # Colored listing as HTML for a web page
+//! fls -l --export ansi-html > listing.html
+//!
+//! # Tree diagram for documentation, with size bars
+//! fls -t -L 2 --export svg --scale-by-size > tree.svg
+//!
 //! # All options combined
 //! fls -lai /path/to/directory
 //! ```
 
+mod changes;
+mod checks;
 mod colors;
+mod comments;
 mod config;
+mod dir_hash;
+mod dir_size;
 mod display;
+mod exec_column;
+mod export;
 mod file_info;
+mod filter;
 mod formatting;
+mod git_ignore;
+mod icons;
+mod logging;
+mod openers;
+mod parallel;
+mod preview;
+mod sort;
+mod spinner;
+mod statx;
+mod terminal;
+mod virtual_fs;
+
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand};
+use colored::Colorize;
+use config::{Aggregate, ColorMode, ColumnAlignment, ColumnPreset, Config, DelimitedFormat, DirLinks, ExportFormat, GroupBy, HeatBy, LinkSizes, SortBy, Tiebreak, TimeColumns};
+use file_info::FileInfo;
+use formatting::{DisplayLocale, DisplayTimeZone};
+use std::ffi::OsString;
+use std::io::{self, IsTerminal};
+use std::iter;
+use std::path::PathBuf;
+use std::time::Duration;
+use tabled::Tabled;
+use tracing::{debug, warn};
 
-use clap::Parser;
-use config::Config;
+/// Grid width with `-C` when neither `--width`, `COLUMNS`, nor a terminal gives one
+const DEFAULT_GRID_WIDTH: usize = 80;
 
 #[derive(Parser)]
 #[command(name = "fls")]
 #[command(version)]
 #[command(about = "Enhanced ls command with detailed permissions, table display, and tree view")]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(args_override_self = true)]
 struct Args {
-    /// Directory path to list
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Directories and files to list; directories are listed as sections when more than one path is given
     #[arg(default_value = ".")]
-    path: String,
+    paths: Vec<PathBuf>,
 
     /// Show hidden files
     #[arg(short = 'a', long = "all")]
     all: bool,
 
+    /// Show hidden files and the . and .. entries with their metadata (like ls -a)
+    #[arg(long = "all-all")]
+    all_all: bool,
+
     /// Show detailed information in table format
     #[arg(short = 'l', long = "long")]
     long: bool,
@@ -76,26 +324,724 @@ struct Args {
     #[arg(short = 'i', long = "interactive")]
     interactive: bool,
 
+    /// Link directories in the interactive tree to file:// URLs or to fls:// URLs that re-list them (see `fls open`)
+    #[arg(long = "dir-links", value_name = "KIND", value_enum, default_value_t = DirLinks::File, requires = "interactive")]
+    dir_links: DirLinks,
+
     /// Display files in a tree-like structure
     #[arg(short = 't', long = "tree")]
     tree: bool,
 
-    /// Limit tree depth to specified number of levels (like tree -L)
+    /// List subdirectories recursively, each as its own section with a `path:` header (like ls -R)
+    #[arg(short = 'R', long = "recursive", conflicts_with_all = ["tree", "json", "format", "export"])]
+    recursive: bool,
+
+    /// Omit the `path:` headers above the sections of a recursive or multi-path listing
+    #[arg(long = "no-headers")]
+    no_headers: bool,
+
+    /// Write entries as a JSON array with raw sizes, numeric IDs, mode bits, and ISO-8601 times (for jq and scripts)
+    #[arg(short = 'j', long = "json", conflicts_with_all = ["tree", "export", "low_memory"])]
+    json: bool,
+
+    /// Add each JSON entry's 1-based position in the sort order as a "rank" field
+    #[arg(long = "rank", requires = "json")]
+    rank: bool,
+
+    /// Limit recursive modes (tree view, live tree, SVG export, -R) to DEPTH levels (like tree -L)
     #[arg(short = 'L', long = "depth", value_name = "DEPTH", value_parser = clap::value_parser!(u8).range(1..=50))]
     depth: Option<u8>,
+
+    /// In the tree, list subdirectories first and show at most N files per directory, with a (+K files) line for the rest
+    #[arg(long = "collapse-files", value_name = "N", requires = "tree", conflicts_with = "export")]
+    collapse_files: Option<usize>,
+
+    /// In the tree, show the number of subdirectories and files after each directory name
+    #[arg(long = "dir-counts", requires = "tree")]
+    dir_counts: bool,
+
+    /// Keep the tree on screen and update it live as files change (requires --tree)
+    #[arg(long = "watch", requires = "tree")]
+    watch: bool,
+
+    /// Leave User/Group blank when they match the invoking user, so foreign-owned files stand out
+    #[arg(long = "smart-owner")]
+    smart_owner: bool,
+
+    /// Highlight permissions that deviate from the expected modes (world-writable, execute without read, setuid)
+    #[arg(long = "highlight-perms")]
+    highlight_perms: bool,
+
+    /// Expected file permissions for --highlight-perms, in octal
+    #[arg(long = "expected-file-mode", value_name = "MODE", default_value = "644", value_parser = parse_mode, requires = "highlight_perms")]
+    expected_file_mode: u32,
+
+    /// Expected directory permissions for --highlight-perms, in octal
+    #[arg(long = "expected-dir-mode", value_name = "MODE", default_value = "755", value_parser = parse_mode, requires = "highlight_perms")]
+    expected_dir_mode: u32,
+
+    /// Hide a table column by header name, e.g. --hide group (repeatable)
+    #[arg(long = "hide", value_name = "COLUMN", value_parser = parse_column_name)]
+    hide: Vec<String>,
+
+    /// Wrap long file names in table format at WIDTH characters (default: 40)
+    #[arg(long = "wrap", value_name = "WIDTH", num_args = 0..=1, default_missing_value = "40", value_parser = clap::value_parser!(u16).range(8..))]
+    wrap: Option<u16>,
+
+    /// Set the alignment of a table column, e.g. `--align name=right` (repeatable)
+    #[arg(long = "align", value_name = "COLUMN=left|right|center", value_parser = parse_column_alignment)]
+    align: Vec<(String, ColumnAlignment)>,
+
+    /// Append a totals row (entry count and cumulative size) to the table format
+    #[arg(long = "summary-row")]
+    summary_row: bool,
+
+    /// Totals shown in the summary row, comma-separated (default: count,size, plus allocated with --disk-usage-delta)
+    #[arg(long = "aggregates", value_name = "WHICH", value_enum, value_delimiter = ',', requires = "summary_row")]
+    aggregates: Option<Vec<Aggregate>>,
+
+    /// Draw the table without separator lines between rows (implies --long)
+    #[arg(long = "compact")]
+    compact: bool,
+
+    /// Preview the first LINES lines of small text files (a table column, or below the name)
+    #[arg(long = "preview", value_name = "LINES", value_parser = clap::value_parser!(u8).range(1..))]
+    preview: Option<u8>,
+
+    /// Layout width for table and grid output (overrides the COLUMNS environment variable and the terminal width)
+    #[arg(long = "width", value_name = "N", value_parser = clap::value_parser!(u16).range(20..))]
+    width: Option<u16>,
+
+    /// List one name per line, even in a terminal (the default when output is piped)
+    #[arg(short = '1', long = "one-per-line", conflicts_with = "columns")]
+    one_per_line: bool,
+
+    /// Pack names into columns even when output is piped (the default in a terminal), like ls -C
+    #[arg(short = 'C', long = "columns")]
+    columns: bool,
+
+    /// Show the recursive size of directory contents in the table's Size column, and sizes in the tree (like du)
+    #[arg(long = "recursive-sizes", visible_alias = "du", conflicts_with = "watch")]
+    recursive_sizes: bool,
+
+    /// Show an Inodes column with recursive inode counts for directories in table format
+    #[arg(long = "inodes")]
+    inodes: bool,
+
+    /// Show a Hash column with a deterministic content hash of each file and directory tree, for comparing copies
+    #[arg(long = "dir-hash")]
+    dir_hash: bool,
+
+    /// Show a Comment column with notes from the user.fls.comment extended attribute or a .fls-notes.toml file in table format
+    #[arg(long = "comments")]
+    comments: bool,
+
+    /// Add a column NAME filled with the first output line of COMMAND run for each entry, e.g. Kind='file -b {}' ({} is the path; repeatable)
+    #[arg(long = "exec-column", value_name = "NAME=COMMAND", value_parser = exec_column::parse_exec_column)]
+    exec_columns: Vec<exec_column::ExecColumn>,
+
+    /// Kill --exec-column commands that run longer than this (e.g. 500ms, 10s)
+    #[arg(long = "exec-timeout", value_name = "DURATION", value_parser = humantime::parse_duration, default_value = "5s", requires = "exec_columns")]
+    exec_timeout: Duration,
+
+    /// Timestamp column(s) to show in table format [default: modified, or the preset's]
+    #[arg(long = "times", value_name = "WHICH", value_enum)]
+    times: Option<TimeColumns>,
+
+    /// Show a curated set of table columns instead of the default ones
+    #[arg(long = "preset", value_name = "PRESET", value_enum)]
+    preset: Option<ColumnPreset>,
+
+    /// Show exact sizes in bytes with thousands separators (e.g. 1,536,224) instead of 1.5M
+    #[arg(long = "bytes")]
+    bytes: bool,
+
+    /// Show the size unit in its own column next to the number (e.g. `1.5 │ K`), so magnitudes line up
+    #[arg(long = "size-split", conflicts_with = "bytes")]
+    size_split: bool,
+
+    /// Show the size and modification time of a symlink's target instead of the link's own (marked with →)
+    #[arg(long = "link-sizes", value_name = "WHOSE", value_enum, default_value_t = LinkSizes::Link)]
+    link_sizes: LinkSizes,
+
+    /// Diff-friendly output for snapshots: tab-separated columns, no colors, fixed-width UTC timestamps, and no locale-dependent formatting
+    #[arg(long = "deterministic")]
+    deterministic: bool,
+
+    /// Write the long listing as CSV or TSV with a header row and raw values (sizes in bytes, ISO-8601 times), for spreadsheets and data pipelines
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        value_enum,
+        conflicts_with_all = ["tree", "json", "export", "group_by", "summary_row"]
+    )]
+    format: Option<DelimitedFormat>,
+
+    /// Write traditional `ls -l` lines (drwxr-xr-x  3 user group  4096 Jun  8 14:30 name) instead of the table, for scripts that expect the conventional layout
+    #[arg(
+        long = "classic",
+        visible_alias = "ls-compat",
+        conflicts_with_all = ["tree", "json", "format", "deterministic", "low_memory"]
+    )]
+    classic: bool,
+
+    /// Show a Nerd Font icon for each entry's file type before its name
+    #[arg(long = "icons")]
+    icons: bool,
+
+    /// Shade every second table row so wide rows are easier to follow
+    #[arg(long = "stripe")]
+    stripe: bool,
+
+    /// Show an Ext column with each file's extension
+    #[arg(long = "ext")]
+    ext: bool,
+
+    /// Show a Heat column with a bar (▁ to █) for each entry's age or size relative to the other entries
+    #[arg(long = "heat", value_name = "BY", value_enum)]
+    heat: Option<HeatBy>,
+
+    /// Group long-format rows by a key, with a separator row per group
+    #[arg(long = "group-by", value_name = "KEY", value_enum)]
+    group_by: Option<GroupBy>,
+
+    /// Show an Allocated column next to Size and highlight files whose allocated and apparent sizes differ significantly (sparse, compressed, or preallocated files)
+    #[arg(long = "disk-usage-delta")]
+    disk_usage_delta: bool,
+
+    /// Show an Age column with the time since modification (e.g. 3d, 7mo, 2y)
+    #[arg(long = "age")]
+    age: bool,
+
+    /// Order entries by name, size (largest first), mtime (newest first), extension, creation time, user, group, or not at all
+    #[arg(long = "sort", value_name = "KEY", value_enum, default_value_t = SortBy::Name)]
+    sort: SortBy,
+
+    /// Reverse the sort order (e.g. smallest first with --sort size)
+    #[arg(short = 'r', long = "reverse", conflicts_with = "sort_column")]
+    reverse: bool,
+
+    /// Order of entries with equal sort keys (e.g. the same size or time)
+    #[arg(long = "tiebreak", value_name = "KEY", value_enum, default_value_t = Tiebreak::Name)]
+    tiebreak: Tiebreak,
+
+    /// Order table rows by any column, e.g. --sort-column octal; the header shows an arrow for the direction
+    #[arg(long = "sort-column", value_name = "COLUMN", value_parser = parse_column_name, conflicts_with_all = ["sort", "json"])]
+    sort_column: Option<String>,
+
+    /// Render timestamps in UTC instead of the local time zone
+    #[arg(long = "utc", global = true, conflicts_with = "timezone")]
+    utc: bool,
+
+    /// Render timestamps in the given IANA time zone (e.g. Europe/Berlin)
+    #[arg(long = "timezone", value_name = "TZ", global = true, value_parser = parse_time_zone)]
+    timezone: Option<chrono_tz::Tz>,
+
+    /// Locale for month names and digit grouping (e.g. de_DE), instead of LC_ALL, LC_TIME, LC_NUMERIC, and LANG
+    #[arg(long = "locale", value_name = "LOCALE", global = true, value_parser = parse_locale_name)]
+    locale: Option<chrono::Locale>,
+
+    /// Only list entries modified within the given duration (e.g. 90min, 2days)
+    #[arg(long = "newer-than", value_name = "DURATION", value_parser = humantime::parse_duration)]
+    newer_than: Option<Duration>,
+
+    /// Hide entries matched by .gitignore, .ignore, .git/info/exclude, and the global git excludes; the tree and -R skip ignored directories such as target/
+    #[arg(long = "git-ignore", conflicts_with = "low_memory")]
+    git_ignore: bool,
+
+    /// Only list entries whose names match PATTERN, where * matches any run of characters, ? one character, and [...] one of a set (repeatable; any may match)
+    #[arg(long = "glob", value_name = "PATTERN", value_parser = filter::parse_name_glob)]
+    globs: Vec<glob::Pattern>,
+
+    /// Only list entries whose names match the regular expression PATTERN (anchor with ^ and $ to match the whole name)
+    #[arg(long = "regex", value_name = "PATTERN", value_parser = filter::parse_name_regex)]
+    regex: Option<regex::Regex>,
+
+    /// Hide entries whose names match a glob pattern (`*`, `?`, and `[...]`, as in --glob; repeatable)
+    #[arg(long = "exclude", value_name = "PATTERN", value_parser = filter::parse_name_glob)]
+    exclude: Vec<glob::Pattern>,
+
+    /// Only list entries last modified at least the given duration ago (e.g. 2weeks)
+    #[arg(long = "older-than", value_name = "DURATION", value_parser = humantime::parse_duration)]
+    older_than: Option<Duration>,
+
+    /// Only list files with any execute bit set (directories are not listed)
+    #[arg(long = "executables")]
+    executables: bool,
+
+    /// Only list directories, like tree -d (symlinks to directories are not listed)
+    #[arg(short = 'd', long = "dirs-only", conflicts_with = "executables")]
+    dirs_only: bool,
+
+    /// Only list files whose contents match PATTERN (a string or regular expression); binary files and files over 16 MiB are skipped
+    #[arg(long = "contains", value_name = "PATTERN", value_parser = filter::parse_contains_pattern)]
+    contains: Option<regex::bytes::Regex>,
+
+    /// Only list entries owned by USER (name or numeric ID)
+    #[arg(long = "user", value_name = "USER")]
+    user: Option<String>,
+
+    /// Only list entries owned by GROUP (name or numeric ID)
+    #[arg(long = "group", value_name = "GROUP")]
+    group: Option<String>,
+
+    /// Hide backup and temporary files (*~, *.swp, .#*, *.tmp), like ls -B
+    #[arg(short = 'B', long = "ignore-backups")]
+    ignore_backups: bool,
+
+    /// Additional name pattern treated as a backup file by -B (repeatable, glob syntax as in --glob)
+    #[arg(long = "backup-pattern", value_name = "PATTERN", value_parser = filter::parse_name_glob, requires = "ignore_backups")]
+    backup_patterns: Vec<glob::Pattern>,
+
+    /// Follow symlinks given as path arguments, but not those found during traversal
+    #[arg(short = 'H', long = "dereference-command-line")]
+    dereference_command_line: bool,
+
+    /// Mark entries modified within the last SECONDS with a ● badge, e.g. the output of a running build
+    #[arg(long = "highlight-new", value_name = "SECONDS")]
+    highlight_new: Option<u64>,
+
+    /// Mark entries whose names differ from a sibling's only by case with a ≈ badge and exit with status 1 if any are found
+    #[arg(long = "check-case-collisions")]
+    check_case_collisions: bool,
+
+    /// Mark names with control characters, a trailing space or dot, a reserved Windows name (CON, NUL, ...), or invalid UTF-8 with a ⚠ badge and exit with status 1 if any are found
+    #[arg(long = "check-names")]
+    check_names: bool,
+
+    /// Mark entries whose user or group no longer exists (orphaned after account removal) with a ⊘ badge and exit with status 1 if any are found
+    #[arg(long = "check-owners")]
+    check_owners: bool,
+
+    /// Mark entries that are new, modified, or removed since the previous --changed run on this path, show their size change, and report owner, group, and mode drift
+    #[arg(long = "changed", conflicts_with_all = ["tree", "json", "classic"])]
+    changed: bool,
+
+    /// Stream entries in directory order as they are read, without sorting, caches, or worker threads; long format is tab-separated
+    #[arg(
+        long = "low-memory",
+        conflicts_with_all = ["tree", "sort", "tiebreak", "sort_column", "group_by", "heat", "summary_row", "changed", "export", "reverse"]
+    )]
+    low_memory: bool,
+
+    /// When to color the output (auto: follow NO_COLOR/CLICOLOR, else only in a terminal)
+    #[arg(long = "color", value_name = "WHEN", value_enum, default_value_t = ColorMode::Auto)]
+    color: ColorMode,
+
+    /// Ignore the configuration file (~/.config/fls/config.toml), including its [defaults]
+    #[arg(long = "no-config")]
+    no_config: bool,
+
+    /// Convert the output to another format (ansi-html: styled HTML spans, svg: tree diagram)
+    #[arg(long = "export", value_name = "FORMAT", value_enum, conflicts_with = "watch")]
+    export: Option<ExportFormat>,
+
+    /// In SVG tree export, add bars proportional to each entry's recursive size
+    #[arg(long = "scale-by-size", requires = "export")]
+    scale_by_size: bool,
+
+    /// Print the format, columns, sorting, filters, and configuration file a listing would use, without reading any directory
+    #[arg(long = "probe")]
+    probe: bool,
+
+    /// Suppress per-entry warnings (only errors are reported)
+    #[arg(short = 'q', long = "quiet", global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Explain filtering and display decisions on stderr (-vv for more detail)
+    #[arg(short = 'v', long = "verbose", global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
 }
 
-fn main() {
+#[derive(Subcommand)]
+enum Command {
+    /// Show a detailed field/value view of a single file (a friendlier stat)
+    Stat {
+        /// File to describe
+        file: PathBuf,
+    },
+    /// List the directory of a clicked fls:// or file:// link as an interactive tree, open a clicked file with its configured command, or print the explanation of a permission link (for terminal URL handlers)
+    Open {
+        /// The link's URL
+        url: String,
+    },
+    /// List the trash with original paths, deletion dates, and sizes
+    Trash,
+}
+
+/// Parses a `COLUMN=ALIGNMENT` pair for the `--align` option.
+///
+/// The column name is checked after parsing, against the table headers and
+/// the `--exec-column` names.
+fn parse_column_alignment(value: &str) -> Result<(String, ColumnAlignment), String> {
+    let (column, alignment) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected COLUMN=ALIGNMENT, got '{}'", value))?;
+
+    let alignment = match alignment.to_ascii_lowercase().as_str() {
+        "left" => ColumnAlignment::Left,
+        "right" => ColumnAlignment::Right,
+        "center" => ColumnAlignment::Center,
+        other => return Err(format!("invalid alignment '{}' (expected left, right, or center)", other)),
+    };
+
+    Ok((column.trim().to_string(), alignment))
+}
+
+/// Parses a table column header name for `--hide`, ignoring case.
+fn parse_column_name(value: &str) -> Result<String, String> {
+    let headers = FileInfo::headers();
+    headers
+        .iter()
+        .find(|header| header.eq_ignore_ascii_case(value.trim()))
+        .map(|header| header.to_string())
+        .ok_or_else(|| {
+            let names: Vec<&str> = headers.iter().map(|header| header.as_ref()).collect();
+            format!("unknown column '{}' (expected one of: {})", value, names.join(", "))
+        })
+}
+
+/// Parses an octal permission mode such as `644` for the expected-mode options.
+fn parse_mode(value: &str) -> Result<u32, String> {
+    u32::from_str_radix(value, 8)
+        .ok()
+        .filter(|&mode| mode <= 0o777)
+        .ok_or_else(|| format!("invalid mode '{}' (expected octal permissions like 644)", value))
+}
+
+/// Parses an IANA time zone name for the `--timezone` option.
+fn parse_time_zone(value: &str) -> Result<chrono_tz::Tz, String> {
+    value
+        .parse()
+        .map_err(|_| format!("unknown time zone '{}' (expected an IANA name like Europe/Berlin)", value))
+}
+
+/// Parses a locale name for the `--locale` option.
+fn parse_locale_name(value: &str) -> Result<chrono::Locale, String> {
+    formatting::parse_locale(value)
+        .ok_or_else(|| format!("unknown locale '{}' (expected a name like de_DE or fr_FR)", value))
+}
+
+/// Reads the layout width from the `COLUMNS` environment variable, ignoring invalid values.
+fn columns_from_env() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|&columns| columns > 0)
+}
+
+/// Expands wildcard patterns in path arguments that the shell left alone, as
+/// Windows `cmd` does and as quoting does (`fls "src/**/*.rs"`).
+///
+/// `*`, `?`, `[...]`, and `**` (any number of directories) are supported, and
+/// wildcards do not match a leading dot, as in shells. Arguments that exist as
+/// given, contain no wildcard, or match nothing are kept unchanged, so a
+/// pattern without matches is reported like any missing path.
+///
+/// # Arguments
+///
+/// * `paths` - The path arguments
+///
+/// # Returns
+///
+/// The paths with each pattern replaced by its matches, in sorted order
+fn expand_path_globs(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let options = glob::MatchOptions {
+        require_literal_leading_dot: true,
+        ..glob::MatchOptions::default()
+    };
+    let mut expanded = Vec::new();
+    for path in paths {
+        let pattern = match path.to_str() {
+            Some(pattern) if pattern.contains(['*', '?', '[']) && path.symlink_metadata().is_err() => pattern,
+            _ => {
+                expanded.push(path);
+                continue;
+            }
+        };
+        let matches: Vec<PathBuf> = match glob::glob_with(pattern, options) {
+            Ok(matches) => matches.filter_map(Result::ok).collect(),
+            Err(e) => {
+                debug!("not expanding {}: {}", pattern, e);
+                Vec::new()
+            }
+        };
+        if matches.is_empty() {
+            expanded.push(path);
+        } else {
+            debug!("expanded {} to {} paths", pattern, matches.len());
+            expanded.extend(matches);
+        }
+    }
+    expanded
+}
+
+/// Parses the command line, preceded by the `[defaults]` of the configuration
+/// file so that options given on the command line override them.
+///
+/// # Arguments
+///
+/// * `config_file` - The configuration file's top-level table
+///
+/// A default that is invalid or conflicts with the command line (say, `-C`
+/// given while the defaults contain `one-per-line`) is skipped, and the others
+/// still apply.
+///
+/// # Returns
+///
+/// The parsed arguments, the defaults that were applied, and each skipped
+/// default with the reason it could not be applied
+fn parse_args(config_file: &toml::Table) -> (Args, Vec<String>, Vec<(String, clap::Error)>) {
     let args = Args::parse();
+    let defaults = config::default_arguments(config_file);
+    // Subcommands take no listing options, so defaults would only conflict with them
+    if args.no_config || args.command.is_some() || defaults.is_empty() {
+        return (args, Vec::new(), Vec::new());
+    }
+
+    let try_parse = |defaults: &[String]| {
+        let mut command_line = std::env::args_os();
+        let program = command_line.next().unwrap_or_else(|| OsString::from("fls"));
+        Args::try_parse_from(
+            iter::once(program)
+                .chain(defaults.iter().map(OsString::from))
+                .chain(command_line),
+        )
+    };
+    if let Ok(args) = try_parse(&defaults) {
+        return (args, defaults, Vec::new());
+    }
+
+    let mut applied = Vec::new();
+    let mut skipped = Vec::new();
+    for default in defaults {
+        applied.push(default);
+        if let Err(e) = try_parse(&applied) {
+            skipped.push((applied.pop().unwrap_or_default(), e));
+        }
+    }
+    let args = try_parse(&applied).unwrap_or(args);
+    (args, applied, skipped)
+}
+
+fn main() {
+    let loaded_config_file = config::load_config_file();
+    let (mut args, config_defaults, skipped_defaults) =
+        parse_args(loaded_config_file.as_ref().unwrap_or(&toml::Table::new()));
+    colors::init_color_mode();
+    match args.color {
+        ColorMode::Always => colored::control::set_override(true),
+        ColorMode::Never => colored::control::set_override(false),
+        ColorMode::Auto => {}
+    }
+    logging::init(args.verbose, args.quiet);
+
+    let config_file = match loaded_config_file {
+        _ if args.no_config => toml::Table::new(),
+        Ok(table) => table,
+        Err(e) => {
+            warn!("ignoring {}", e);
+            toml::Table::new()
+        }
+    };
+    colors::init_theme(&config_file);
+    for (default, e) in skipped_defaults {
+        let message = e.to_string();
+        let reason = message.lines().next().unwrap_or_default().trim_start_matches("error: ");
+        // Overriding a default on the command line is expected; a broken default is not
+        if e.kind() == ErrorKind::ArgumentConflict {
+            debug!("not applying {} from the configuration file: {}", default, reason);
+        } else {
+            warn!("ignoring {} from the configuration file: {}", default, reason);
+        }
+    }
+
+    if args.export == Some(ExportFormat::Svg) && !args.tree {
+        Args::command()
+            .error(ErrorKind::MissingRequiredArgument, "--export svg requires --tree")
+            .exit();
+    }
 
+    // --align may also name --exec-column columns, which are only known once every argument is parsed
+    for (column, _) in &mut args.align {
+        if args.exec_columns.iter().any(|exec| exec.name.eq_ignore_ascii_case(column)) {
+            continue;
+        }
+        match parse_column_name(column) {
+            Ok(name) => *column = name,
+            Err(e) => Args::command()
+                .error(ErrorKind::InvalidValue, format!("invalid value for '--align': {}", e))
+                .exit(),
+        }
+    }
+
+    args.paths = expand_path_globs(args.paths);
+    if args.watch && args.paths.len() > 1 {
+        Args::command()
+            .error(ErrorKind::ArgumentConflict, "--watch follows a single path")
+            .exit();
+    }
+    // A data file has one header row, so sections of several listings cannot share it
+    if args.format.is_some() && args.paths.len() > 1 {
+        Args::command()
+            .error(ErrorKind::ArgumentConflict, "--format lists a single path")
+            .exit();
+    }
+
+    // Exported output is usually redirected, but the colors are the point of exporting it
+    if args.export == Some(ExportFormat::AnsiHtml) {
+        colored::control::set_override(true);
+    }
+    // Snapshots and data files must not depend on whether they were written in a terminal
+    if args.deterministic || args.format.is_some() {
+        colored::control::set_override(false);
+    }
+
+    let width = args.width.map(|w| w as usize).or_else(columns_from_env);
+    let preset = args.preset;
+    let preset_shows = |column| preset.is_some_and(|preset| preset.shows(column));
     let config = Config {
-        path: args.path,
-        long_format: args.long,
-        show_hidden: args.all,
+        path: args.paths[0].to_string_lossy().to_string(),
+        long_format: args.long || args.compact || args.format.is_some() || args.classic,
+        show_hidden: args.all || args.all_all,
+        dot_entries: args.all_all,
         interactive: args.interactive,
+        dir_links: args.dir_links,
+        json: args.json,
+        rank: args.rank,
+        recursive: args.recursive,
+        section_headers: !args.no_headers,
+        no_config: args.no_config,
+        config_defaults,
+        openers: openers::Openers::from_config(&config_file),
+        size_thresholds: colors::SizeThresholds::from_config(&config_file),
         tree: args.tree,
-        tree_depth: args.depth.map(|d| d as usize),
+        depth: args.depth.map(|d| d as usize),
+        collapse_files: args.collapse_files,
+        dir_counts: args.dir_counts,
+        wrap_width: args.wrap.map(|w| w as usize),
+        column_alignments: args.align,
+        summary_row: args.summary_row,
+        aggregates: args.aggregates.unwrap_or_else(|| {
+            let mut aggregates = vec![Aggregate::Count, Aggregate::Size];
+            if args.disk_usage_delta || preset_shows("Allocated") {
+                aggregates.push(Aggregate::Allocated);
+            }
+            aggregates
+        }),
+        compact: args.compact,
+        preview: args.preview.map(usize::from),
+        width,
+        // Multi-line entries and streamed output cannot be laid out in columns
+        grid_width: (!args.one_per_line
+            && (args.columns || io::stdout().is_terminal())
+            && args.preview.is_none()
+            && !args.changed
+            && !args.low_memory)
+            .then(|| width.or_else(terminal::width).unwrap_or(DEFAULT_GRID_WIDTH)),
+        recursive_sizes: args.recursive_sizes,
+        inodes: args.inodes,
+        inode_numbers: preset_shows("Inode"),
+        preset,
+        dir_hash: args.dir_hash || preset_shows("Hash"),
+        comments: args.comments,
+        exec_columns: args.exec_columns,
+        exec_timeout: args.exec_timeout,
+        smart_owner: args.smart_owner,
+        highlight_permissions: args.highlight_perms,
+        expected_file_mode: args.expected_file_mode,
+        expected_dir_mode: args.expected_dir_mode,
+        hidden_columns: args.hide,
+        times: args.times.or(preset.map(ColumnPreset::times)).unwrap_or_default(),
+        exact_bytes: args.bytes,
+        size_split: args.size_split,
+        link_sizes: args.link_sizes,
+        deterministic: args.deterministic,
+        format: args.format,
+        classic: args.classic,
+        stripe: args.stripe,
+        show_ext: args.ext || preset_shows("Ext"),
+        icons: args.icons.then(|| icons::Icons::from_config(&config_file)),
+        group_by: args.group_by,
+        heat: args.heat,
+        highlight_new: args.highlight_new.map(Duration::from_secs),
+        check_case_collisions: args.check_case_collisions,
+        check_names: args.check_names,
+        check_owners: args.check_owners,
+        disk_usage_delta: args.disk_usage_delta || preset_shows("Allocated"),
+        show_age: args.age || preset_shows("Age"),
+        sort: args.sort,
+        reverse: args.reverse,
+        tiebreak: args.tiebreak,
+        sort_column: args.sort_column,
+        time_zone: match (args.utc, args.timezone) {
+            (true, _) => DisplayTimeZone::Utc,
+            (false, Some(tz)) => DisplayTimeZone::Named(tz),
+            (false, None) => DisplayTimeZone::Local,
+        },
+        locale: args.locale.map_or_else(DisplayLocale::from_env, DisplayLocale::uniform),
+        git_ignore: args.git_ignore,
+        name_globs: args.globs,
+        name_regex: args.regex,
+        exclude_patterns: args.exclude,
+        newer_than: args.newer_than,
+        older_than: args.older_than,
+        executables_only: args.executables,
+        dirs_only: args.dirs_only,
+        contains: args.contains,
+        owner_user: args.user,
+        owner_group: args.group,
+        ignore_backups: args.ignore_backups,
+        backup_patterns: filter::DEFAULT_BACKUP_PATTERNS
+            .iter()
+            .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+            .chain(args.backup_patterns)
+            .collect(),
+        dereference_args: args.dereference_command_line,
+        changed: args.changed,
+        low_memory: args.low_memory,
+        watch: args.watch,
+        export: args.export,
+        scale_by_size: args.scale_by_size,
     };
 
-    display::list_directory(&config);
+    if args.probe {
+        display::probe::display(&args.paths, &config);
+        return;
+    }
+
+    match args.command {
+        Some(Command::Stat { file }) => display::stat::display(&file, &config),
+        Some(Command::Open { url }) => {
+            if let Some(explanation) = colors::explanation_from_url(&url) {
+                println!("{}", explanation);
+                return;
+            }
+            let Some(path) = colors::path_from_url(&url) else {
+                Args::command()
+                    .error(ErrorKind::InvalidValue, format!("not an fls:// or file:// URL: {}", url))
+                    .exit();
+            };
+            if !path.is_dir() {
+                if let Err(e) = config.openers.open(&path) {
+                    eprintln!("{}: {}: {}", "Error".red().bold(), path.display(), e);
+                }
+                return;
+            }
+            // Keep re-listing on further clicks
+            display::list_directory(&Config {
+                path: path.to_string_lossy().to_string(),
+                tree: true,
+                interactive: true,
+                dir_links: DirLinks::Fls,
+                ..config
+            });
+        }
+        Some(Command::Trash) => display::trash::display(&config),
+        None => display::list_paths(&args.paths, &config),
+    }
+
+    if checks::checks_failed() {
+        std::process::exit(1);
+    }
 }
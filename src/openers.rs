@@ -0,0 +1,146 @@
+//! Per-extension applications for opening files from interactive links.
+//!
+//! The `[open]` section of the configuration file maps file extensions to
+//! shell commands that `fls open` runs instead of the system's default
+//! handler:
+//!
+//! ```toml
+//! [open]
+//! md = "glow"
+//! json = "jq -C . {} | less -R"
+//! ```
+//!
+//! `{}` in a command is replaced with the file's path; without it, the path
+//! is appended as the last argument. Extensions match case-insensitively and
+//! may be written with or without the leading dot.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use tracing::{debug, warn};
+
+/// Placeholder replaced with the file's path in an opener command
+const PATH_PLACEHOLDER: &str = "{}";
+
+/// Commands that open files, by extension.
+#[derive(Clone, Default)]
+pub struct Openers {
+    /// Shell commands by lowercase extension without the dot
+    commands: HashMap<String, String>,
+}
+
+impl Openers {
+    /// Reads the `[open]` section of the configuration file.
+    ///
+    /// A missing section means no custom openers. Values that are not strings
+    /// are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The parsed configuration file (see
+    ///   [`load_config_file`](crate::config::load_config_file))
+    ///
+    /// # Returns
+    ///
+    /// The configured openers
+    pub fn from_config(file: &toml::Table) -> Self {
+        let Some(toml::Value::Table(open)) = file.get("open") else {
+            return Self::default();
+        };
+        let commands = open
+            .iter()
+            .filter_map(|(extension, value)| match value {
+                toml::Value::String(command) => {
+                    Some((extension.trim_start_matches('.').to_lowercase(), command.clone()))
+                }
+                _ => {
+                    warn!("ignoring non-string opener for '{}' in the configuration file", extension);
+                    None
+                }
+            })
+            .collect();
+        Self { commands }
+    }
+
+    /// Returns the extensions that have a configured command, sorted.
+    ///
+    /// # Returns
+    ///
+    /// The lowercase extensions without the dot
+    pub fn extensions(&self) -> Vec<&str> {
+        let mut extensions: Vec<&str> = self.commands.keys().map(String::as_str).collect();
+        extensions.sort_unstable();
+        extensions
+    }
+
+    /// Returns the command configured for a file's extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the file
+    ///
+    /// # Returns
+    ///
+    /// The shell command, or None if the extension has no opener
+    pub fn command_for(&self, path: &Path) -> Option<&str> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+        self.commands.get(&extension).map(String::as_str)
+    }
+
+    /// Opens a file with its configured command, or with the system's default
+    /// handler if its extension has none.
+    ///
+    /// The command runs through `sh -c` attached to the terminal, so pagers
+    /// and other interactive programs work, and this waits for it to exit.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command or default handler cannot be started,
+    /// or if the command exits unsuccessfully.
+    pub fn open(&self, path: &Path) -> io::Result<()> {
+        let Some(command) = self.command_for(path) else {
+            debug!("opening {} with the default handler", path.display());
+            return open::that(path);
+        };
+
+        let command_line = command_line(command, path);
+        debug!("opening {} with: {}", path.display(), command_line);
+
+        let status = Command::new("sh").arg("-c").arg(&command_line).status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("'{}' failed ({})", command_line, status)))
+        }
+    }
+}
+
+/// Builds the shell command line that runs a command on a file.
+///
+/// # Arguments
+///
+/// * `command` - The configured command, optionally containing `{}`
+/// * `path` - Path to the file
+///
+/// # Returns
+///
+/// The command with `{}` replaced by the quoted path, or with the quoted path
+/// appended if it has no `{}`
+pub fn command_line(command: &str, path: &Path) -> String {
+    let quoted = shell_quote(&path.to_string_lossy());
+    if command.contains(PATH_PLACEHOLDER) {
+        command.replace(PATH_PLACEHOLDER, &quoted)
+    } else {
+        format!("{} {}", command, quoted)
+    }
+}
+
+/// Quotes a string for `sh` by wrapping it in single quotes.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
@@ -0,0 +1,92 @@
+//! Ordered parallel computation for expensive per-entry work.
+//!
+//! Items are processed by a pool of worker threads that each claim the next
+//! unprocessed item, so a single slow item does not hold up a fixed batch of
+//! others. Results are handed back in input order as soon as every earlier
+//! result is ready, which lets streaming output start before the slowest item
+//! finishes while keeping the listing's sort order.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Computes `compute(item)` for every item in parallel and passes the results
+/// to `emit` in input order.
+///
+/// `emit` runs on the calling thread and is called for each result as soon as
+/// it and all results before it are available.
+///
+/// # Arguments
+///
+/// * `items` - The items to process, in output order
+/// * `compute` - The expensive computation, run on worker threads
+/// * `emit` - Receives the results in input order
+///
+/// # Panics
+///
+/// Propagates a panic from `compute` once the remaining workers have finished.
+pub fn for_each_ordered<T, R, F, E>(items: &[T], compute: F, mut emit: E)
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+    E: FnMut(R),
+{
+    if items.is_empty() {
+        return;
+    }
+
+    let workers = thread::available_parallelism().map_or(1, |n| n.get()).min(items.len());
+    let next_item = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let (next_item, compute) = (&next_item, &compute);
+            scope.spawn(move || loop {
+                let index = next_item.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                if sender.send((index, compute(item))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // Results that arrived before an earlier one, keyed by item index
+        let mut pending = BTreeMap::new();
+        let mut next_to_emit = 0;
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&next_to_emit) {
+                emit(result);
+                next_to_emit += 1;
+            }
+        }
+    });
+}
+
+/// Computes `compute(item)` for every item in parallel and collects the results.
+///
+/// # Arguments
+///
+/// * `items` - The items to process
+/// * `compute` - The expensive computation, run on worker threads
+///
+/// # Returns
+///
+/// The results in the same order as `items`
+pub fn map_ordered<T, R, F>(items: &[T], compute: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let mut results = Vec::with_capacity(items.len());
+    for_each_ordered(items, compute, |result| results.push(result));
+    results
+}
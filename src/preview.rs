@@ -0,0 +1,85 @@
+//! Content previews of small text files.
+//!
+//! This module reads the first lines of small text files for `--preview`, so
+//! directories full of short config or note files can be skimmed without
+//! opening each one. Large and binary files are never previewed.
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::virtual_fs::is_virtual;
+
+/// Files larger than this are not previewed
+const MAX_FILE_SIZE: u64 = 64 * 1024;
+
+/// Number of bytes read from the start of a file at most
+const READ_LIMIT: u64 = 4096;
+
+/// Preview lines wider than this many columns are truncated with an ellipsis
+const MAX_LINE_WIDTH: usize = 40;
+
+/// Reads the first lines of a small text file.
+///
+/// Control characters are replaced with spaces so file contents cannot inject
+/// terminal escape sequences, and long lines are truncated unless `truncate`
+/// is false (for machine-readable output).
+///
+/// # Arguments
+///
+/// * `path` - Path to the file
+/// * `metadata` - The file's metadata
+/// * `lines` - Maximum number of lines to return
+/// * `truncate` - Whether to shorten long lines with an ellipsis
+///
+/// # Returns
+///
+/// The preview lines, or `None` if the entry is not a regular file, is too
+/// large, on a virtual filesystem such as `/proc`, cannot be read, or does not
+/// look like text
+pub fn preview_lines(path: &Path, metadata: &fs::Metadata, lines: usize, truncate: bool) -> Option<Vec<String>> {
+    if !metadata.is_file() || metadata.len() > MAX_FILE_SIZE || is_virtual(path) {
+        return None;
+    }
+
+    let mut buffer = Vec::new();
+    File::open(path).ok()?.take(READ_LIMIT).read_to_end(&mut buffer).ok()?;
+
+    // NUL bytes mean binary content
+    if buffer.contains(&0) {
+        return None;
+    }
+    let text = match std::str::from_utf8(&buffer) {
+        Ok(text) => text,
+        // The read limit may have cut a multi-byte character in half
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&buffer[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+
+    Some(text.lines().take(lines).map(|line| clean_line(line, truncate)).collect())
+}
+
+/// Replaces control characters and, if requested, truncates the line to the preview width.
+fn clean_line(line: &str, truncate: bool) -> String {
+    let line: String = line
+        .trim_end()
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+
+    if truncate && line.width() > MAX_LINE_WIDTH {
+        // Wide characters take two columns, so count columns rather than characters
+        let mut width = 0;
+        let truncated: String = line
+            .chars()
+            .take_while(|c| {
+                width += c.width().unwrap_or(0);
+                width < MAX_LINE_WIDTH
+            })
+            .collect();
+        format!("{}…", truncated)
+    } else {
+        line
+    }
+}
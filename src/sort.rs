@@ -0,0 +1,160 @@
+//! Entry ordering shared by all display formats.
+//!
+//! This module sorts directory entries according to `--sort`, so simple, table,
+//! and tree output list entries in the same order. Entries with equal keys are
+//! ordered by `--tiebreak`: by name (the default) or by modification time and
+//! then name, both of which give the same order on every run, so listings can
+//! be diffed. `--tiebreak none` keeps the order the filesystem returned, which
+//! sorting never disturbs since it is stable. `-r/--reverse` reverses the
+//! comparison of keys, tiebreaks included; with `--sort none` every key is
+//! equal, so the filesystem order is kept, as with `ls -U -r`.
+
+use std::cmp::{Ordering, Reverse};
+use std::ffi::OsString;
+use std::fs::{self, DirEntry};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use std::time::SystemTime;
+use tracing::warn;
+
+use crate::config::{Config, SortBy, Tiebreak};
+use crate::file_info::{file_extension, group_name, user_name};
+
+/// The part of a sort key that comes before the name tiebreak.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Primary {
+    None,
+    /// Newest first; entries without the timestamp sort last
+    Time(Reverse<Option<SystemTime>>),
+    /// Largest first
+    Size(Reverse<u64>),
+    Text(String),
+}
+
+/// The parts of a sort key, compared in order.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct KeyParts {
+    primary: Primary,
+    /// Modification time for `--tiebreak mtime`, newest first
+    modified: Option<Reverse<Option<SystemTime>>>,
+    /// None for `--tiebreak none`, so equal keys keep their original order
+    name: Option<OsString>,
+}
+
+/// Comparable sort key of one entry.
+#[derive(PartialEq, Eq)]
+pub struct EntryKey {
+    parts: KeyParts,
+    /// Whether the order is reversed with `-r/--reverse`
+    reverse: bool,
+}
+
+impl Ord for EntryKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ordering = self.parts.cmp(&other.parts);
+        if self.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+impl PartialOrd for EntryKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Computes the sort key of the entry at `path` without following symlinks.
+///
+/// # Arguments
+///
+/// * `path` - Path to the entry
+/// * `config` - Configuration specifying the sort order
+///
+/// # Returns
+///
+/// A key that orders entries as requested by `--sort`, `--tiebreak`, and `--reverse`
+pub fn path_key(path: &Path, config: &Config) -> EntryKey {
+    let metadata = || fs::symlink_metadata(path).ok();
+    let primary = match config.sort {
+        SortBy::Name | SortBy::None => Primary::None,
+        SortBy::Size => Primary::Size(Reverse(metadata().map_or(0, |metadata| metadata.len()))),
+        SortBy::Ext => Primary::Text(
+            metadata()
+                .map(|metadata| file_extension(path, &metadata).to_lowercase())
+                .unwrap_or_default(),
+        ),
+        SortBy::Created => Primary::Time(Reverse(metadata().and_then(|metadata| metadata.created().ok()))),
+        SortBy::Mtime => Primary::Time(Reverse(metadata().and_then(|metadata| metadata.modified().ok()))),
+        SortBy::User => Primary::Text(metadata().map(|metadata| user_name(metadata.uid())).unwrap_or_default()),
+        SortBy::Group => Primary::Text(metadata().map(|metadata| group_name(metadata.gid())).unwrap_or_default()),
+    };
+
+    let name = path.file_name().map(|name| name.to_os_string()).unwrap_or_default();
+    let (modified, name) = match (config.sort, config.tiebreak) {
+        // Directory order: every key is equal, so the stable sort keeps it
+        (SortBy::None, _) => (None, None),
+        // Names are the sort key itself here, not a tiebreak
+        (SortBy::Name, _) | (_, Tiebreak::Name) => (None, Some(name)),
+        (_, Tiebreak::Mtime) => (
+            Some(Reverse(metadata().and_then(|metadata| metadata.modified().ok()))),
+            Some(name),
+        ),
+        (_, Tiebreak::None) => (None, None),
+    };
+
+    EntryKey {
+        parts: KeyParts { primary, modified, name },
+        reverse: config.reverse,
+    }
+}
+
+/// Sorts directory entries in place.
+///
+/// # Arguments
+///
+/// * `entries` - The entries to sort
+/// * `config` - Configuration specifying the sort order
+pub fn sort_entries(entries: &mut [DirEntry], config: &Config) {
+    entries.sort_by_cached_key(|entry| path_key(&entry.path(), config));
+    warn_if_unsupported(entries.iter().map(|entry| entry.path()), config);
+}
+
+/// Sorts the results of reading a directory in place.
+///
+/// Entries that could not be read sort first, so the displays report them
+/// before listing the rest.
+///
+/// # Arguments
+///
+/// * `entries` - The directory read results to sort
+/// * `config` - Configuration specifying the sort order
+pub fn sort_entry_results(entries: &mut [io::Result<DirEntry>], config: &Config) {
+    entries.sort_by_cached_key(|entry| entry.as_ref().ok().map(|entry| path_key(&entry.path(), config)));
+    warn_if_unsupported(entries.iter().filter_map(|entry| entry.as_ref().ok()).map(|entry| entry.path()), config);
+}
+
+/// Explains the fallback to name order when no entry has a creation time.
+///
+/// The warning is given once per run, even when several directories are sorted.
+fn warn_if_unsupported(paths: impl Iterator<Item = PathBuf>, config: &Config) {
+    static WARNING: Once = Once::new();
+
+    if config.sort != SortBy::Created {
+        return;
+    }
+
+    let mut paths = paths.peekable();
+    if paths.peek().is_none() {
+        return;
+    }
+    if !paths.any(|path| fs::symlink_metadata(path).and_then(|metadata| metadata.created()).is_ok()) {
+        WARNING.call_once(|| {
+            warn!("creation time is not available on this platform or filesystem; sorted by name instead");
+        });
+    }
+}
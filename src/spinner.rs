@@ -0,0 +1,75 @@
+//! Progress spinner for long-running computations.
+//!
+//! This module provides a minimal stderr spinner shown while expensive work
+//! (such as recursive directory sizing) is in progress. It only draws when
+//! stderr is a capable terminal, so piped and redirected output stays clean.
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Animation frames drawn in sequence
+const FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Delay between animation frames
+const FRAME_INTERVAL: Duration = Duration::from_millis(80);
+
+/// A running spinner that is cleared from the terminal when dropped.
+pub struct Spinner {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    /// Starts a spinner with the given message on stderr.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Text shown next to the spinner
+    ///
+    /// # Returns
+    ///
+    /// A Spinner that stops and clears its line when dropped. If stderr is not
+    /// a terminal nothing is drawn.
+    pub fn start(message: &str) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+
+        // A dumb terminal cannot clear the spinner line afterwards
+        if !io::stderr().is_terminal() || std::env::var("TERM").is_ok_and(|term| term == "dumb") {
+            return Self { running, handle: None };
+        }
+
+        let message = message.to_string();
+        let flag = Arc::clone(&running);
+        let handle = thread::spawn(move || {
+            let mut stderr = io::stderr();
+            for frame in FRAMES.iter().cycle() {
+                if !flag.load(Ordering::Relaxed) {
+                    break;
+                }
+                let _ = write!(stderr, "\r{} {}", frame, message);
+                let _ = stderr.flush();
+                thread::sleep(FRAME_INTERVAL);
+            }
+            // Clear the spinner line
+            let _ = write!(stderr, "\r\x1b[2K");
+            let _ = stderr.flush();
+        });
+
+        Self {
+            running,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
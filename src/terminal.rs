@@ -0,0 +1,93 @@
+//! Terminal size and on-screen text width.
+//!
+//! The grid layout of the simple view packs names into as many columns as
+//! the terminal is wide, which needs the terminal's width and the width names
+//! take on screen once their color and hyperlink escape sequences are removed.
+//! The table view strips the same sequences to inspect its rendered cells.
+
+use std::mem::MaybeUninit;
+use unicode_width::UnicodeWidthStr;
+
+/// Escape character that starts color (CSI) and hyperlink (OSC) sequences
+const ESCAPE: char = '\u{1b}';
+
+/// Bell character that may terminate an OSC sequence
+const BELL: char = '\u{7}';
+
+/// Returns the width of the terminal standard output is attached to.
+///
+/// # Returns
+///
+/// The number of columns, or None if standard output is not a terminal
+pub fn width() -> Option<usize> {
+    let mut size = MaybeUninit::<libc::winsize>::uninit();
+    // SAFETY: TIOCGWINSZ writes a `winsize` into the provided buffer
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, size.as_mut_ptr()) };
+    if result != 0 {
+        return None;
+    }
+    // SAFETY: the ioctl succeeded, so it initialized `size`
+    let size = unsafe { size.assume_init() };
+    Some(usize::from(size.ws_col)).filter(|&columns| columns > 0)
+}
+
+/// Returns the number of columns a string takes on screen.
+///
+/// CSI sequences (colors) and OSC sequences (hyperlinks) take no space,
+/// wide characters (CJK, most emoji) take two columns, and combining marks
+/// none, as in the table's cell widths.
+///
+/// # Arguments
+///
+/// * `text` - Text that may contain escape sequences
+///
+/// # Returns
+///
+/// The visible width
+pub fn visible_width(text: &str) -> usize {
+    strip_escapes(text).width()
+}
+
+/// Removes the CSI sequences (colors) and OSC sequences (hyperlinks) from a string.
+///
+/// # Arguments
+///
+/// * `text` - Text that may contain escape sequences
+///
+/// # Returns
+///
+/// The text as shown on screen
+pub fn strip_escapes(text: &str) -> String {
+    let mut visible = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != ESCAPE {
+            visible.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters up to a final byte in @..~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: up to BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == BELL {
+                        break;
+                    }
+                    if c == ESCAPE && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    visible
+}
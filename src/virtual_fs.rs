@@ -0,0 +1,123 @@
+//! Detection of virtual filesystems such as `/proc` and `/sys`.
+//!
+//! Files on these filesystems are generated by the kernel on access: their
+//! sizes are meaningless (usually 0 or 4096), reading them can block forever
+//! (`/proc/kmsg`) or have side effects, and trees like `/proc` are huge and
+//! self-referential. Size computation, hashing, and content reads skip them,
+//! and the table labels them with the filesystem name instead of a size.
+
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs;
+use std::mem::MaybeUninit;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// Filesystem type magic numbers (from `linux/magic.h`) of kernel-generated filesystems
+#[cfg(target_os = "linux")]
+const VIRTUAL_FILESYSTEMS: &[(u64, &str)] = &[
+    (0x9fa0, "procfs"),
+    (0x6265_6572, "sysfs"),
+    (0x1cd1, "devpts"),
+    (0x6462_6720, "debugfs"),
+    (0x7472_6163, "tracefs"),
+    (0x7363_6673, "securityfs"),
+    (0x27e0eb, "cgroup"),
+    (0x6367_7270, "cgroup2"),
+    (0x6265_6570, "configfs"),
+    (0xcafe_4a11, "bpf"),
+    (0x6165_676c, "pstore"),
+    (0xde5e_81e4, "efivarfs"),
+    (0x1980_0202, "mqueue"),
+];
+
+/// Returns the name of the virtual filesystem a path lives on, if any.
+///
+/// Symlinks are followed, so a link into `/proc` counts as virtual. Only
+/// Linux filesystems are recognized; elsewhere this always returns None.
+///
+/// # Arguments
+///
+/// * `path` - The path to check
+///
+/// # Returns
+///
+/// The filesystem name, such as "procfs" or "sysfs", or None for regular
+/// filesystems and paths that cannot be checked
+pub fn virtual_filesystem(path: &Path) -> Option<&'static str> {
+    let filesystem_type = filesystem_type(path)?;
+    virtual_filesystem_name(filesystem_type)
+}
+
+/// Returns the process-wide cache of the virtual filesystem of each device.
+fn device_cache() -> &'static Mutex<HashMap<u64, Option<&'static str>>> {
+    static CACHE: OnceLock<Mutex<HashMap<u64, Option<&'static str>>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Returns the name of the virtual filesystem a listed entry lives on, if any.
+///
+/// Unlike [`virtual_filesystem`], this asks `statfs` only once per device,
+/// so listing a large directory costs one call rather than one per entry.
+/// Symlinks are not followed and never count as virtual.
+///
+/// # Arguments
+///
+/// * `path` - Path to the entry
+/// * `metadata` - The entry's metadata, whose device ID keys the cache
+///
+/// # Returns
+///
+/// The filesystem name, such as "procfs" or "sysfs", or None for regular
+/// filesystems, symlinks, and entries that cannot be checked
+pub fn entry_filesystem(path: &Path, metadata: &fs::Metadata) -> Option<&'static str> {
+    if metadata.file_type().is_symlink() {
+        return None;
+    }
+    let mut cache = device_cache().lock().unwrap_or_else(|e| e.into_inner());
+    *cache.entry(metadata.dev()).or_insert_with(|| virtual_filesystem(path))
+}
+
+/// Checks whether a path lives on a virtual filesystem.
+///
+/// # Arguments
+///
+/// * `path` - The path to check
+///
+/// # Returns
+///
+/// `true` for paths on filesystems such as procfs and sysfs
+pub fn is_virtual(path: &Path) -> bool {
+    virtual_filesystem(path).is_some()
+}
+
+#[cfg(target_os = "linux")]
+fn virtual_filesystem_name(filesystem_type: u64) -> Option<&'static str> {
+    VIRTUAL_FILESYSTEMS
+        .iter()
+        .find(|(magic, _)| *magic == filesystem_type)
+        .map(|(_, name)| *name)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn virtual_filesystem_name(_filesystem_type: u64) -> Option<&'static str> {
+    None
+}
+
+/// Returns the `f_type` reported by `statfs(2)`.
+fn filesystem_type(path: &Path) -> Option<u64> {
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stats = MaybeUninit::<libc::statfs>::uninit();
+    // SAFETY: `path` is NUL-terminated and `stats` is large enough for the result
+    let result = unsafe { libc::statfs(path.as_ptr(), stats.as_mut_ptr()) };
+    if result != 0 {
+        return None;
+    }
+    // SAFETY: statfs succeeded, so it initialized `stats`
+    let stats = unsafe { stats.assume_init() };
+    // The field's integer type differs between platforms
+    #[allow(clippy::unnecessary_cast)]
+    Some(stats.f_type as u64)
+}